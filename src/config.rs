@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the config file that is looked up in the current directory
+pub const CONFIG_FILE_NAME: &str = "mclint.toml";
+/// Name of the config file that is looked up in the home directory
+pub const HOME_CONFIG_FILE_NAME: &str = ".mclint.toml";
+
+/// Settings read from `mclint.toml`. Everything that
+/// is missing from the file gets its default value.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub format: FormatConfig
+}
+
+/// The `[format]` table, used by `mclint fmt`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatConfig {
+    /// Number of spaces per nesting level
+    pub indent_width: usize,
+    pub brace_style: BraceStyle,
+    /// Lists in calls, arrays, dictionaries and parameters
+    /// that don't fit into it are split one item per line
+    pub max_line_width: usize
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self { indent_width: 4, brace_style: BraceStyle::SameLine, max_line_width: 100 }
+    }
+}
+
+/// Where the `{` of classes, functions and blocks goes
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BraceStyle {
+    /// `if (x) {`
+    SameLine,
+    /// `if (x)`, and `{` on the next line
+    NextLine
+}
+
+impl Config {
+    pub fn from_toml(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Reads the config from `path`
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read contents of {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Reads `mclint.toml` from the current directory, or
+    /// `.mclint.toml` from the home directory if there's no
+    /// such file. Without either, the defaults are used.
+    pub fn find() -> Result<Self> {
+        match Self::find_path() {
+            Some(path) => Self::read(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn find_path() -> Option<PathBuf> {
+        let local = PathBuf::from(CONFIG_FILE_NAME);
+        if local.is_file() {
+            return Some(local);
+        }
+        home::home_dir().map(|home| home.join(HOME_CONFIG_FILE_NAME)).filter(|path| path.is_file())
+    }
+}
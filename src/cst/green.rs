@@ -0,0 +1,108 @@
+use crate::lexer::tokens::{TokenKind, TriviaKind};
+use serde::Serialize;
+use std::fmt;
+use std::rc::Rc;
+
+/// Kinds of the inner nodes of the tree. Leaves are always tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NodeKind {
+    /// The root of every tree
+    SourceFile,
+    /// Everything between `{` and `}`, including both
+    BracketGroup,
+    /// Everything between `(` and `)`, including both
+    BraceGroup,
+    /// Everything between `[` and `]`, including both
+    SquareBracketGroup
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GreenTrivia {
+    pub kind: TriviaKind,
+    pub text: String
+}
+
+/// A token together with the trivia around it. Green
+/// elements don't know where they are in the file, so
+/// equal subtrees can be shared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenToken {
+    pub kind: TokenKind,
+    /// Exact source text of the token
+    pub text: String,
+    pub leading_trivia: Vec<GreenTrivia>,
+    pub trailing_trivia: Vec<GreenTrivia>
+}
+
+impl GreenToken {
+    /// Length in bytes of the leading trivia
+    pub fn leading_len(&self) -> usize {
+        self.leading_trivia.iter().map(|t| t.text.len()).sum()
+    }
+
+    /// Length in bytes of the token with all of its trivia
+    pub fn full_len(&self) -> usize {
+        self.leading_len() + self.text.len() + self.trailing_trivia.iter().map(|t| t.text.len()).sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>)
+}
+
+impl GreenElement {
+    pub fn full_len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.full_len(),
+            GreenElement::Token(token) => token.full_len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenNode {
+    pub kind: NodeKind,
+    pub children: Vec<GreenElement>,
+    full_len: usize
+}
+
+impl GreenNode {
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Self {
+        let full_len = children.iter().map(|c| c.full_len()).sum();
+        Self { kind, children, full_len }
+    }
+
+    /// Length in bytes of all the text under the node, trivia included
+    pub fn full_len(&self) -> usize {
+        self.full_len
+    }
+}
+
+/// Prints the exact source text the token was made from
+impl fmt::Display for GreenToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in &self.leading_trivia {
+            f.write_str(&trivia.text)?;
+        }
+        f.write_str(&self.text)?;
+        for trivia in &self.trailing_trivia {
+            f.write_str(&trivia.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the exact source text the node was made from
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.fmt(f)?,
+                GreenElement::Token(token) => token.fmt(f)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! A lossless bracket tree of a source file.
+//!
+//! Every token keeps the whitespace and comments around it, so
+//! printing the tree gives back the file byte for byte. The only
+//! structure in it is the nesting of `{}`, `()` and `[]` though.
+//! It knows nothing about declarations, statements or expressions,
+//! which are found in the AST of the parser instead.
+
+use crate::cst::green::{GreenElement, GreenNode, GreenToken, GreenTrivia, NodeKind};
+use crate::cst::red::SyntaxNode;
+use crate::lexer::tokens::{Token, TokenKind, Trivia};
+use std::rc::Rc;
+
+pub mod green;
+pub mod red;
+
+/// Builds the bracket tree of `source` out of `tokens`,
+/// which must be the result of lexing it.
+///
+/// A closing bracket that doesn't match the innermost open
+/// group is kept as a plain token, and groups that are never
+/// closed end right before the end of the file.
+pub fn build(source: &str, tokens: &[Token]) -> SyntaxNode {
+    // Open groups, innermost last. The root is at the bottom.
+    let mut stack: Vec<(NodeKind, Vec<GreenElement>)> = vec![(NodeKind::SourceFile, Vec::new())];
+
+    for token in tokens {
+        if token.kind == TokenKind::Eof {
+            break;
+        }
+        let green = GreenElement::Token(Rc::new(green_token(source, token)));
+        match group_opened_by(&token.kind) {
+            Some(kind) => stack.push((kind, vec![green])),
+            None => {
+                let closes_group = stack.len() > 1 && Some(stack[stack.len() - 1].0) == group_closed_by(&token.kind);
+                if let Some((_, children)) = stack.last_mut() {
+                    children.push(green);
+                }
+                if closes_group {
+                    close_group(&mut stack);
+                }
+            }
+        }
+    }
+
+    while stack.len() > 1 {
+        close_group(&mut stack);
+    }
+    let (_, mut children) = stack.pop().unwrap_or((NodeKind::SourceFile, Vec::new()));
+    if let Some(eof) = tokens.iter().find(|t| t.kind == TokenKind::Eof) {
+        children.push(GreenElement::Token(Rc::new(green_token(source, eof))));
+    }
+    SyntaxNode::new_root(Rc::new(GreenNode::new(NodeKind::SourceFile, children)))
+}
+
+/// Turns the innermost open group into a node of its parent
+fn close_group(stack: &mut Vec<(NodeKind, Vec<GreenElement>)>) {
+    if let Some((kind, children)) = stack.pop() {
+        let node = GreenElement::Node(Rc::new(GreenNode::new(kind, children)));
+        if let Some((_, parent)) = stack.last_mut() {
+            parent.push(node);
+        }
+    }
+}
+
+fn green_token(source: &str, token: &Token) -> GreenToken {
+    let text = source.get(token.span.start..token.span.end).unwrap_or(&token.literal);
+    GreenToken {
+        kind: token.kind.clone(),
+        text: text.to_string(),
+        leading_trivia: token.leading_trivia.iter().map(green_trivia).collect(),
+        trailing_trivia: token.trailing_trivia.iter().map(green_trivia).collect()
+    }
+}
+
+fn green_trivia(trivia: &Trivia) -> GreenTrivia {
+    GreenTrivia { kind: trivia.kind.clone(), text: trivia.text.clone() }
+}
+
+fn group_opened_by(kind: &TokenKind) -> Option<NodeKind> {
+    match kind {
+        TokenKind::OpeningBracket => Some(NodeKind::BracketGroup),
+        TokenKind::OpeningBrace => Some(NodeKind::BraceGroup),
+        TokenKind::OpeningSquareBracket => Some(NodeKind::SquareBracketGroup),
+        _ => None,
+    }
+}
+
+fn group_closed_by(kind: &TokenKind) -> Option<NodeKind> {
+    match kind {
+        TokenKind::ClosingBracket => Some(NodeKind::BracketGroup),
+        TokenKind::ClosingBrace => Some(NodeKind::BraceGroup),
+        TokenKind::ClosingSquareBracket => Some(NodeKind::SquareBracketGroup),
+        _ => None,
+    }
+}
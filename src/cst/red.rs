@@ -0,0 +1,152 @@
+use crate::cst::green::{GreenElement, GreenNode, GreenToken, NodeKind};
+use crate::lexer::tokens::TokenKind;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// A view of a green node that knows its parent and
+/// its position in the file. Red nodes are created
+/// lazily while walking the tree.
+#[derive(Debug, Clone)]
+pub struct SyntaxNode(Rc<SyntaxNodeData>);
+
+#[derive(Debug)]
+struct SyntaxNodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    /// Byte offset of the node's first trivia
+    offset: usize
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        Self(Rc::new(SyntaxNodeData { green, parent: None, offset: 0 }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.0.parent.as_ref()
+    }
+
+    /// Range of the node in the file, trivia included
+    pub fn full_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.full_len()
+    }
+
+    pub fn children(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut children = Vec::new();
+        for child in &self.0.green.children {
+            children.push(match child {
+                GreenElement::Node(node) => SyntaxElement::Node(SyntaxNode(Rc::new(SyntaxNodeData {
+                    green: node.clone(),
+                    parent: Some(self.clone()),
+                    offset
+                }))),
+                GreenElement::Token(token) => SyntaxElement::Token(SyntaxToken {
+                    green: token.clone(),
+                    parent: self.clone(),
+                    offset
+                }),
+            });
+            offset += child.full_len();
+        }
+        children
+    }
+
+    /// All tokens under the node, in source order
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.green.fmt(f)
+    }
+}
+
+/// Nodes are written with their position in the file,
+/// which green nodes don't have
+impl Serialize for SyntaxNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut node = serializer.serialize_struct("SyntaxNode", 3)?;
+        node.serialize_field("kind", &self.kind())?;
+        node.serialize_field("full_range", &self.full_range())?;
+        node.serialize_field("children", &self.children())?;
+        node.end()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    /// Byte offset of the token's first leading trivia
+    offset: usize
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> &TokenKind {
+        &self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn green(&self) -> &Rc<GreenToken> {
+        &self.green
+    }
+
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+
+    /// Range of the token in the file, trivia included
+    pub fn full_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.full_len()
+    }
+
+    /// Range of the token in the file, without trivia
+    pub fn text_range(&self) -> Range<usize> {
+        let start = self.offset + self.green.leading_len();
+        start..start + self.green.text.len()
+    }
+}
+
+impl Serialize for SyntaxToken {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut token = serializer.serialize_struct("SyntaxToken", 6)?;
+        token.serialize_field("kind", self.kind())?;
+        token.serialize_field("text", self.text())?;
+        token.serialize_field("full_range", &self.full_range())?;
+        token.serialize_field("text_range", &self.text_range())?;
+        token.serialize_field("leading_trivia", &self.green.leading_trivia)?;
+        token.serialize_field("trailing_trivia", &self.green.trailing_trivia)?;
+        token.end()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken)
+}
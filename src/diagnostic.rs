@@ -0,0 +1,50 @@
+use crate::lexer::err::MCLexError;
+use crate::parser::err::MCParseError;
+use crate::span::Span;
+use serde::Serialize;
+use std::path::Path;
+
+/// A problem found in a file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Line and column where the problem starts, both 1-based
+    pub at: (u64, u64),
+    /// Exact range of the erroneous part
+    pub span: Span,
+    /// What is wrong, without saying where
+    pub message: String
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum DiagnosticKind {
+    /// Something the lexer couldn't make a token out of
+    BadToken,
+    /// Tokens that don't form valid code
+    SyntaxError
+}
+
+impl Diagnostic {
+    /// The message together with where the problem is,
+    /// the way the command line tool prints it
+    pub fn full_message(&self, file_path: &Path) -> String {
+        let (row, column) = self.at;
+        match self.kind {
+            DiagnosticKind::BadToken => format!("Bad token at {}:{}: {}", row, column, self.message),
+            DiagnosticKind::SyntaxError =>
+                format!("Syntax error at {}:{}:{}: {}", file_path.display(), row, column, self.message),
+        }
+    }
+}
+
+impl From<MCLexError> for Diagnostic {
+    fn from(error: MCLexError) -> Self {
+        Self { kind: DiagnosticKind::BadToken, at: error.at, span: error.span, message: error.msg }
+    }
+}
+
+impl From<MCParseError> for Diagnostic {
+    fn from(error: MCParseError) -> Self {
+        Self { kind: DiagnosticKind::SyntaxError, at: error.at, span: error.span, message: error.msg }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::config::FormatConfig;
+use crate::parser::ast::MonkeyCStatement;
+use crate::parser::printer;
+use crate::Diagnostic;
+use serde_json::Value;
+use std::path::Path;
+
+/// Lines of unchanged code shown around every change in a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// Why a file couldn't be formatted
+#[derive(Debug)]
+pub enum FormatError {
+    /// The file has syntax errors. The parts of it that
+    /// are malformed can't be printed back.
+    Syntax(Vec<Diagnostic>),
+    /// The formatted code would mean something else than the
+    /// original. It's a bug of the formatter, and the file is
+    /// better left alone than broken.
+    ChangesMeaning
+}
+
+/// Formats the code of a file according to `config`
+pub fn format_source(source: &str, config: &FormatConfig) -> Result<String, FormatError> {
+    let (tokens, errors) = crate::lex(source);
+    if !errors.is_empty() {
+        return Err(FormatError::Syntax(errors));
+    }
+    let (statements, errors) = crate::parse_tokens(tokens.clone());
+    if !errors.is_empty() {
+        return Err(FormatError::Syntax(errors));
+    }
+    let formatted = printer::format(source, &tokens, &statements, config);
+
+    let (formatted_statements, errors) = crate::parse(&formatted);
+    if !errors.is_empty() || !same_tree(&statements, &formatted_statements) {
+        return Err(FormatError::ChangesMeaning);
+    }
+    Ok(formatted)
+}
+
+/// Returns true if both trees are the same apart from spans
+pub(crate) fn same_tree(original: &[MonkeyCStatement], formatted: &[MonkeyCStatement]) -> bool {
+    without_spans(original) == without_spans(formatted)
+}
+
+fn without_spans(statements: &[MonkeyCStatement]) -> Value {
+    fn strip(value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                fields.remove("span");
+                fields.values_mut().for_each(strip);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::json!(statements);
+    strip(&mut value);
+    value
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Change {
+    Same,
+    Removed,
+    Added
+}
+
+/// Describes how to get from `original` to `formatted`
+/// as a unified diff, or returns an empty string if
+/// they are the same. Lines are compared together with
+/// their line breaks, so `\r\n` and `\n` are different.
+pub fn diff(file_path: &Path, original: &str, formatted: &str) -> String {
+    let old: Vec<&str> = original.split_inclusive('\n').collect();
+    let new: Vec<&str> = formatted.split_inclusive('\n').collect();
+    let lines = diff_lines(&old, &new);
+
+    // Line numbers in both versions where every line of the diff is
+    let mut positions = Vec::with_capacity(lines.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for (change, _) in &lines {
+        positions.push((old_line, new_line));
+        match change {
+            Change::Same => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Change::Removed => old_line += 1,
+            Change::Added => new_line += 1,
+        }
+    }
+
+    let mut out = String::new();
+    let mut i = 0;
+    while let Some(first_change) = lines[i..].iter().position(|(change, _)| *change != Change::Same) {
+        let start = (i + first_change).saturating_sub(DIFF_CONTEXT);
+        // A hunk goes on while the changes in it are close enough
+        let mut end = i + first_change;
+        loop {
+            let next_same = lines[end..].iter().position(|(change, _)| *change == Change::Same).map_or(lines.len(), |p| end + p);
+            match lines[next_same..].iter().position(|(change, _)| *change != Change::Same) {
+                Some(gap) if gap <= DIFF_CONTEXT * 2 => end = next_same + gap,
+                _ => {
+                    end = (next_same + DIFF_CONTEXT).min(lines.len());
+                    break;
+                }
+            }
+        }
+
+        if out.is_empty() {
+            out.push_str(&format!("--- {}\n+++ {}\n", file_path.display(), file_path.display()));
+        }
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|(change, _)| *change != Change::Added).count();
+        let new_len = hunk.iter().filter(|(change, _)| *change != Change::Removed).count();
+        let (old_start, new_start) = positions[start];
+        // Empty ranges point to the line before them
+        let old_start = if old_len == 0 { old_start } else { old_start + 1 };
+        let new_start = if new_len == 0 { new_start } else { new_start + 1 };
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len));
+        for (change, line) in hunk {
+            let marker = match change {
+                Change::Same => ' ',
+                Change::Removed => '-',
+                Change::Added => '+',
+            };
+            out.push(marker);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        i = end;
+    }
+    out
+}
+
+/// Finds the longest common subsequence of lines, after skipping
+/// the lines both versions start and end with
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Change, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // common[i][j] is the length of the common subsequence of old_middle[i..] and new_middle[j..]
+    let width = new_middle.len() + 1;
+    let mut common = vec![0u32; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            common[i * width + j] = if old_middle[i] == new_middle[j] {
+                common[(i + 1) * width + j + 1] + 1
+            } else {
+                common[(i + 1) * width + j].max(common[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<(Change, &str)> = old[..prefix].iter().map(|line| (Change::Same, *line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            lines.push((Change::Same, old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if j == new_middle.len() || (i < old_middle.len() && common[(i + 1) * width + j] >= common[i * width + j + 1]) {
+            lines.push((Change::Removed, old_middle[i]));
+            i += 1;
+        } else {
+            lines.push((Change::Added, new_middle[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|line| (Change::Same, *line)));
+    lines
+}
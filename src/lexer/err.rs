@@ -0,0 +1,11 @@
+use crate::span::Span;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct MCLexError {
+    pub at: (u64, u64),
+    /// Exact range of the erroneous part
+    pub span: Span,
+    pub full_msg: String,
+    pub msg: String,
+}
@@ -1,245 +1,731 @@
-use crate::lexer::tokens::{Token, TokenKind};
-use anyhow::Result;
-use anyhow::bail;
-
+use crate::lexer::err::MCLexError;
+use crate::lexer::tokens::{Token, TokenKind, TokenValue, Trivia, TriviaKind};
+use crate::span::{FileId, Span};
 
 pub mod tokens;
+pub mod err;
+
+/// Every operator of the language. Longer operators go
+/// before their prefixes, so the first match is always
+/// the longest one.
+const OPERATORS: &[(&str, TokenKind)] = &[
+    ("<<=", TokenKind::LeftShiftAssign),
+    (">>=", TokenKind::RightShiftAssign),
+    ("==", TokenKind::Equals),
+    ("!=", TokenKind::NotEquals),
+    ("<=", TokenKind::LessThanEquals),
+    (">=", TokenKind::GreaterThanEquals),
+    ("&&", TokenKind::DoubleAmpersand),
+    ("||", TokenKind::DoubleVerticalBar),
+    ("<<", TokenKind::LeftShift),
+    (">>", TokenKind::RightShift),
+    ("+=", TokenKind::PlusAssign),
+    ("-=", TokenKind::MinusAssign),
+    ("*=", TokenKind::AsteriskAssign),
+    ("/=", TokenKind::SlashAssign),
+    ("%=", TokenKind::PercentAssign),
+    ("&=", TokenKind::AmpersandAssign),
+    ("|=", TokenKind::VerticalBarAssign),
+    ("^=", TokenKind::CaretAssign),
+    ("++", TokenKind::Increment),
+    ("--", TokenKind::Decrement),
+    ("?:", TokenKind::Elvis),
+    ("=>", TokenKind::Arrow),
+    ("=", TokenKind::Assign),
+    ("!", TokenKind::Bang),
+    ("~", TokenKind::Tilde),
+    ("+", TokenKind::Plus),
+    ("-", TokenKind::Minus),
+    ("*", TokenKind::Asterisk),
+    ("/", TokenKind::Slash),
+    ("%", TokenKind::Percent),
+    ("&", TokenKind::Ampersand),
+    ("|", TokenKind::VerticalBar),
+    ("^", TokenKind::Caret),
+    ("<", TokenKind::LessThan),
+    (">", TokenKind::GreaterThan),
+    ("?", TokenKind::QuestionMark),
+];
 
 #[derive(Debug)]
 pub struct MonkeyCLexer {
     source: Vec<char>,
+    file_id: FileId,
     currently_at: usize,
+    /// Same as `currently_at`, but in bytes
+    current_offset: usize,
     current_column: u64,
-    current_row: u64
+    current_row: u64,
+    errors: Vec<MCLexError>,
+    /// Number of `?` that haven't met their `:` yet. Used
+    /// to tell `a ? b :c` apart from a `:c` symbol.
+    pending_ternaries: usize,
+    /// Trivia seen since the last line break. It's attached
+    /// to the next token as its leading trivia.
+    pending_trivia: Vec<Trivia>,
+    /// Whether there was no line break since the last token.
+    /// Trivia found in this state is the token's trailing trivia.
+    on_token_line: bool
 }
 
 impl MonkeyCLexer {
     pub fn new(source: Vec<char>) -> Self {
+        Self::with_file_id(source, 0)
+    }
+
+    /// Creates a lexer whose spans will point to the file `file_id`
+    pub fn with_file_id(source: Vec<char>, file_id: FileId) -> Self {
         Self {
             source,
+            file_id,
             currently_at: 0,
+            current_offset: 0,
             current_column: 1,
-            current_row: 1
+            current_row: 1,
+            errors: Vec::new(),
+            pending_ternaries: 0,
+            pending_trivia: Vec::new(),
+            on_token_line: false
         }
     }
 
     fn next(&mut self) {
+        let breaks_line = match self.source.get(self.currently_at) {
+            Some('\n') => true,
+            // In `\r\n`, the row changes after the `\n`
+            Some('\r') => self.source.get(self.currently_at + 1) != Some(&'\n'),
+            _ => false,
+        };
+        if breaks_line {
+            self.current_row += 1;
+            self.current_column = 1;
+        } else {
+            self.current_column += 1;
+        }
+        self.current_offset += self.source.get(self.currently_at).map_or(0, |c| c.len_utf8());
         self.currently_at += 1;
-        self.current_column += 1;
     }
 
-    pub fn lex(&mut self) -> Result<Vec<Token>> {
+    fn position(&self) -> Position {
+        Position {
+            row: self.current_row,
+            column: self.current_column,
+            index: self.currently_at,
+            offset: self.current_offset
+        }
+    }
+
+    /// Returns a span from `from` to the current position
+    fn span_from(&self, from: &Position) -> Span {
+        Span::new(self.file_id, from.offset, self.current_offset)
+    }
+
+    /// Returns a character `offset` characters after the current one
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.source.get(self.currently_at + offset).copied()
+    }
+
+    /// Pushes `token` to `tokens`, attaching all trivia
+    /// collected since the previous line break to it
+    fn push(&mut self, tokens: &mut Vec<Token>, mut token: Token) {
+        token.leading_trivia = std::mem::take(&mut self.pending_trivia);
+        tokens.push(token);
+        self.on_token_line = true;
+    }
+
+    /// Attaches `trivia` to the previous token if it's on the same
+    /// line (up to and including the line break), or to the next one
+    fn push_trivia(&mut self, tokens: &mut [Token], trivia: Trivia) {
+        let ends_line = trivia.kind == TriviaKind::Newline;
+        if self.on_token_line {
+            self.on_token_line = !ends_line;
+            if let Some(token) = tokens.last_mut() {
+                token.trailing_trivia.push(trivia);
+                return;
+            }
+        }
+        self.pending_trivia.push(trivia);
+    }
+
+    /// Consumes a run of whitespace other than line breaks
+    fn lex_whitespace(&mut self) -> Trivia {
+        let start = self.position();
+        let mut buffer = String::new();
+        while let Some(c) = self.peek(0).filter(|c| c.is_whitespace() && *c != '\n' && *c != '\r') {
+            buffer.push(c);
+            self.next();
+        }
+        let mut trivia = Trivia::new(TriviaKind::Whitespace, buffer, start.row, start.column);
+        trivia.span = self.span_from(&start);
+        trivia
+    }
+
+    /// Consumes a line break, which is either `\n`, `\r\n` or `\r`
+    fn lex_newline(&mut self) -> Trivia {
+        let start = self.position();
+        let mut buffer = String::new();
+        if self.peek(0) == Some('\r') {
+            buffer.push('\r');
+            self.next();
+        }
+        if self.peek(0) == Some('\n') {
+            buffer.push('\n');
+            self.next();
+        }
+        let mut trivia = Trivia::new(TriviaKind::Newline, buffer, start.row, start.column);
+        trivia.span = self.span_from(&start);
+        trivia
+    }
+
+    /// Consumes an identifier or a keyword, returning its text.
+    /// Must only be called when the current character starts an identifier.
+    fn scan_identifier(&mut self) -> String {
+        let mut buffer = String::new();
+        while let Some(c) = self.peek(0).filter(|c| c.is_alphanumeric() || *c == '_') {
+            buffer.push(c);
+            self.next();
+        }
+        buffer
+    }
+
+    /// Whether a `:` glued to an identifier closes a ternary
+    /// operator. That's the case when there is an unmatched `?`
+    /// and the colon comes right after an operand, like in `a ? b :c`.
+    fn is_ternary_colon(&self, previous: Option<&Token>) -> bool {
+        let after_operand = previous.is_some_and(|t| matches!(t.kind,
+            TokenKind::Identifier | TokenKind::BoolLiteral | TokenKind::StringLiteral | TokenKind::IntLiteral |
+            TokenKind::LongLiteral | TokenKind::FloatLiteral | TokenKind::DoubleLiteral | TokenKind::CharLiteral |
+            TokenKind::SymbolLiteral | TokenKind::Null | TokenKind::Nan | TokenKind::Me | TokenKind::Self_ |
+            TokenKind::ClosingBrace | TokenKind::ClosingSquareBracket));
+        self.pending_ternaries > 0 && after_operand
+    }
+
+    /// Consumes the longest operator starting at the current character.
+    /// Must only be called when the current character starts some operator.
+    fn lex_operator(&mut self, tokens: &mut Vec<Token>) {
+        let row = self.current_row;
+        let column = self.current_column;
+        for (operator, kind) in OPERATORS {
+            let matches = operator.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c));
+            if matches {
+                for _ in 0..operator.len() {
+                    self.next();
+                }
+                self.push(tokens, Token::new(kind.clone(), operator.to_string(), row, column));
+                return;
+            }
+        }
+    }
+
+    /// Consumes a numeric literal. Malformed literals are still
+    /// turned into tokens (without a value), so lexing can go on,
+    /// but an error is recorded for each of them.
+    fn lex_number(&mut self, tokens: &mut Vec<Token>) {
+        let start = self.position();
+        let mut buffer = String::new();
+        let mut kind = TokenKind::IntLiteral;
+        let mut radix = 10;
+        let mut error: Option<String> = None;
+
+        if self.peek(0) == Some('0') && matches!(self.peek(1), Some('x') | Some('X')) {
+            radix = 16;
+            self.next();
+            self.next();
+            while let Some(c) = self.peek(0).filter(|c| c.is_ascii_hexdigit()) {
+                buffer.push(c);
+                self.next();
+            }
+            if buffer.is_empty() {
+                error = Some("A hexadecimal literal must have at least one digit".to_string());
+            }
+            if self.peek(0) == Some('l') {
+                kind = TokenKind::LongLiteral;
+                self.next();
+            }
+        } else {
+            while let Some(c) = self.peek(0).filter(|c| c.is_ascii_digit()) {
+                buffer.push(c);
+                self.next();
+            }
+
+            // A fraction needs a digit after the dot, so that `5.format()` stays a call
+            if self.peek(0) == Some('.') && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
+                kind = TokenKind::FloatLiteral;
+                buffer.push('.');
+                self.next();
+                while let Some(c) = self.peek(0).filter(|c| c.is_ascii_digit()) {
+                    buffer.push(c);
+                    self.next();
+                }
+            }
+
+            if matches!(self.peek(0), Some('e') | Some('E')) {
+                let signed = matches!(self.peek(1), Some('+') | Some('-'));
+                let first_digit = if signed { self.peek(2) } else { self.peek(1) };
+                if first_digit.is_some_and(|c| c.is_ascii_digit()) {
+                    kind = TokenKind::FloatLiteral;
+                    buffer.push('e');
+                    self.next();
+                    if signed {
+                        buffer.push(self.peek(0).unwrap_or('+'));
+                        self.next();
+                    }
+                    while let Some(c) = self.peek(0).filter(|c| c.is_ascii_digit()) {
+                        buffer.push(c);
+                        self.next();
+                    }
+                }
+            }
+
+            match self.peek(0) {
+                Some('l') => {
+                    if kind == TokenKind::FloatLiteral {
+                        error = Some("A Float can't have 'l' at the end".to_string());
+                    }
+                    kind = TokenKind::LongLiteral;
+                    self.next();
+                }
+                Some('d') => {
+                    kind = TokenKind::DoubleLiteral;
+                    self.next();
+                }
+                Some('f') => {
+                    kind = TokenKind::FloatLiteral;
+                    self.next();
+                }
+                _ => {}
+            }
+
+            // Numbers with a leading zero are octal, like in C
+            if matches!(kind, TokenKind::IntLiteral | TokenKind::LongLiteral) && buffer.len() > 1 && buffer.starts_with('0') {
+                radix = 8;
+                if let Some(digit) = buffer.chars().find(|c| *c > '7') {
+                    error = Some(format!("Invalid digit '{}' in an octal literal", digit));
+                }
+            }
+        }
+
+        // Anything glued to the number, like `12abc` or `5ld`
+        let mut suffix = String::new();
+        while let Some(c) = self.peek(0).filter(|c| c.is_alphanumeric() || *c == '_') {
+            suffix.push(c);
+            self.next();
+        }
+        if !suffix.is_empty() {
+            error = Some(if matches!(suffix.chars().next(), Some('l') | Some('d') | Some('f')) {
+                "Multiple number decorators are forbidden".to_string()
+            } else {
+                format!("Invalid suffix '{}' on a numeric literal", suffix)
+            });
+        }
+
+        let literal: String = self.source[start.index..self.currently_at].iter().collect();
+        let value = match error {
+            Some(msg) => {
+                self.error(&start, msg);
+                None
+            }
+            None => match parse_number_value(&kind, &buffer, radix) {
+                Ok(value) => Some(value),
+                Err(msg) => {
+                    self.error(&start, msg);
+                    None
+                }
+            }
+        };
+        let mut token = Token::new(kind, literal, start.row, start.column);
+        token.value = value;
+        self.push(tokens, token);
+    }
+
+    /// Consumes a literal enclosed in `quote`s, which can't span
+    /// multiple lines. Returns the raw text between the quotes
+    /// and the text with escape sequences decoded. The latter is
+    /// `None` if the literal is unterminated or has bad escapes.
+    fn lex_quoted(&mut self, quote: char) -> (String, Option<String>) {
+        let start = self.position();
+        let mut raw = String::new();
+        let mut cooked = String::new();
+        let mut valid = true;
+        self.next();
+
+        loop {
+            match self.peek(0) {
+                Some(c) if c == quote => {
+                    self.next();
+                    return (raw, Some(cooked).filter(|_| valid));
+                }
+                None | Some('\n') | Some('\r') => {
+                    let what = if quote == '"' { "String" } else { "Char" };
+                    self.error(&start, format!("Unterminated {} literal", what));
+                    return (raw, None);
+                }
+                Some('\\') => {
+                    let escape = self.position();
+                    self.next();
+                    match self.lex_escape() {
+                        Ok(c) => cooked.push(c),
+                        Err(msg) => {
+                            self.error(&escape, msg);
+                            valid = false;
+                        }
+                    }
+                    raw.extend(&self.source[escape.index..self.currently_at]);
+                }
+                Some(c) => {
+                    raw.push(c);
+                    cooked.push(c);
+                    self.next();
+                }
+            }
+        }
+    }
+
+    /// Decodes an escape sequence. The backslash
+    /// must already be consumed.
+    fn lex_escape(&mut self) -> std::result::Result<char, String> {
+        let c = match self.peek(0) {
+            Some(c) if c != '\n' => c,
+            _ => return Err("Expected an escape sequence after '\\'".to_string()),
+        };
+        self.next();
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            '0' => Ok('\0'),
+            '\\' | '\'' | '"' => Ok(c),
+            'u' => {
+                let high = self.lex_unicode_escape_digits()?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| format!("Invalid Unicode escape '\\u{:04X}'", high));
+                }
+                // A high surrogate must be followed by a low one
+                if self.peek(0) != Some('\\') || self.peek(1) != Some('u') {
+                    return Err(format!("Unpaired surrogate in Unicode escape '\\u{:04X}'", high));
+                }
+                self.next();
+                self.next();
+                let low = self.lex_unicode_escape_digits()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(format!("Unpaired surrogate in Unicode escape '\\u{:04X}'", high));
+                }
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                char::from_u32(code).ok_or_else(|| "Invalid Unicode escape".to_string())
+            }
+            _ => Err(format!("Unknown escape sequence '\\{}'", c)),
+        }
+    }
+
+    /// Reads the four hex digits of a `\uXXXX` escape
+    fn lex_unicode_escape_digits(&mut self) -> std::result::Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.peek(0).and_then(|c| c.to_digit(16)) {
+                Some(digit) => {
+                    code = code * 16 + digit;
+                    self.next();
+                }
+                None => return Err("A Unicode escape must have exactly four hexadecimal digits".to_string()),
+            }
+        }
+        Ok(code)
+    }
+
+    /// Records a lexical error spanning from `from` to the current position
+    fn error(&mut self, from: &Position, msg: String) {
+        self.errors.push(MCLexError {
+            at: (from.row, from.column),
+            span: self.span_from(from),
+            full_msg: format!("Bad token at {}:{}: {}", from.row, from.column, msg),
+            msg
+        });
+    }
+
+    /// Consumes a `// ...` comment up to (but not including) the line break
+    fn lex_line_comment(&mut self) -> Trivia {
+        let start = self.position();
+        let mut buffer = String::new();
+        while let Some(c) = self.peek(0) {
+            if c == '\n' || c == '\r' {
+                break;
+            }
+            buffer.push(c);
+            self.next();
+        }
+        let mut trivia = Trivia::new(TriviaKind::LineComment, buffer, start.row, start.column);
+        trivia.span = self.span_from(&start);
+        trivia
+    }
+
+    /// Consumes a `/* ... */` comment. Block comments can't be nested.
+    /// An unterminated comment swallows the rest of the file.
+    fn lex_block_comment(&mut self) -> Trivia {
+        let start = self.position();
+        let mut buffer = String::from("/*");
+        self.next();
+        self.next();
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some('*'), Some('/')) => {
+                    buffer.push_str("*/");
+                    self.next();
+                    self.next();
+                    break;
+                }
+                (Some(c), _) => {
+                    buffer.push(c);
+                    self.next();
+                }
+                (None, _) => {
+                    self.error(&start, "Unterminated block comment".to_string());
+                    break;
+                }
+            }
+        }
+        let mut trivia = Trivia::new(TriviaKind::BlockComment, buffer, start.row, start.column);
+        trivia.span = self.span_from(&start);
+        trivia
+    }
+
+    /// Splits the source into tokens. Lexing never fails: characters
+    /// that can't start a token become `Error` tokens, and every
+    /// problem found along the way is returned as an error.
+    pub fn lex(&mut self) -> (Vec<Token>, Vec<MCLexError>) {
         let mut tokens: Vec<Token> = Vec::new();
-        while self.source.len() > self.currently_at {
-            let c = self.current_char();
+        while let Some(c) = self.peek(0) {
+            let start = self.position();
+            let token_count = tokens.len();
 
             match c {
-                '\n' => {
-                    self.current_row += 1;
-                    self.current_column = 1;
-                    self.currently_at += 1;
+                '\n' | '\r' => {
+                    let trivia = self.lex_newline();
+                    self.push_trivia(&mut tokens, trivia);
                 }
                 '{' => {
-                    tokens.push(Token::new(TokenKind::OpeningBracket, c.to_string(), self.current_row, self.current_column));
+                    self.pending_ternaries = 0;
+                    self.push(&mut tokens, Token::new(TokenKind::OpeningBracket, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
                 '}' => {
-                    tokens.push(Token::new(TokenKind::ClosingBracket, c.to_string(), self.current_row, self.current_column));
+                    self.pending_ternaries = 0;
+                    self.push(&mut tokens, Token::new(TokenKind::ClosingBracket, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
                 '(' => {
-                    tokens.push(Token::new(TokenKind::OpeningBrace, c.to_string(), self.current_row, self.current_column));
+                    self.push(&mut tokens, Token::new(TokenKind::OpeningBrace, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
                 ')' => {
-                    tokens.push(Token::new(TokenKind::ClosingBrace, c.to_string(), self.current_row, self.current_column));
+                    self.push(&mut tokens, Token::new(TokenKind::ClosingBrace, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '~' => {
-                    tokens.push(Token::new(TokenKind::Tilde, c.to_string(), self.current_row, self.current_column));
-                    self.next();
+                '/' if self.peek(1) == Some('/') => {
+                    let trivia = self.lex_line_comment();
+                    self.push_trivia(&mut tokens, trivia);
                 }
-                '=' => {
-                    tokens.push(Token::new(TokenKind::Assign, c.to_string(), self.current_row, self.current_column));
-                    self.next();
+                '/' if self.peek(1) == Some('*') => {
+                    let trivia = self.lex_block_comment();
+                    self.push_trivia(&mut tokens, trivia);
                 }
-                '-' => {
-                    tokens.push(Token::new(TokenKind::Minus, c.to_string(), self.current_row, self.current_column));
-                    self.next();
+                '~' | '=' | '-' | '+' | '/' | '*' | '%' | '!' | '^' | '>' | '<' | '&' | '|' => {
+                    self.lex_operator(&mut tokens);
                 }
-                '+' => {
-                    tokens.push(Token::new(TokenKind::Plus, c.to_string(), self.current_row, self.current_column));
+                // `a ?:b : c` is a ternary with a symbol, not an Elvis operator
+                '?' if self.peek(1) == Some(':') && self.peek(2).is_some_and(is_identifier_start) => {
+                    self.pending_ternaries += 1;
+                    self.push(&mut tokens, Token::new(TokenKind::QuestionMark, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '/' => {
-                    tokens.push(Token::new(TokenKind::Slash, c.to_string(), self.current_row, self.current_column));
-                    self.next();
+                '?' => {
+                    if self.peek(1) != Some(':') {
+                        self.pending_ternaries += 1;
+                    }
+                    self.lex_operator(&mut tokens);
                 }
                 ',' => {
-                    tokens.push(Token::new(TokenKind::Comma, c.to_string(), self.current_row, self.current_column));
+                    self.push(&mut tokens, Token::new(TokenKind::Comma, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
                 ';' => {
-                    tokens.push(Token::new(TokenKind::Semicolon, c.to_string(), self.current_row, self.current_column));
+                    self.pending_ternaries = 0;
+                    self.push(&mut tokens, Token::new(TokenKind::Semicolon, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '!' => {
-                    tokens.push(Token::new(TokenKind::Bang, c.to_string(), self.current_row, self.current_column));
+                '.' => {
+                    self.push(&mut tokens, Token::new(TokenKind::Dot, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '^' => {
-                    tokens.push(Token::new(TokenKind::Caret, c.to_string(), self.current_row, self.current_column));
+                ':' if self.peek(1).is_some_and(is_identifier_start) && !self.is_ternary_colon(tokens.last()) => {
+                    let row = self.current_row;
+                    let column = self.current_column;
                     self.next();
+                    let name = self.scan_identifier();
+                    self.push(&mut tokens, Token::new(TokenKind::SymbolLiteral, name, row, column));
                 }
-                '>' => {
-                    tokens.push(Token::new(TokenKind::GreaterThan, c.to_string(), self.current_row, self.current_column));
+                ':' => {
+                    self.pending_ternaries = self.pending_ternaries.saturating_sub(1);
+                    self.push(&mut tokens, Token::new(TokenKind::Colon, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '<' => {
-                    tokens.push(Token::new(TokenKind::LessThan, c.to_string(), self.current_row, self.current_column));
+                '[' => {
+                    self.push(&mut tokens, Token::new(TokenKind::OpeningSquareBracket, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '&' => {
-                    tokens.push(Token::new(TokenKind::Ampersand, c.to_string(), self.current_row, self.current_column));
+                ']' => {
+                    self.push(&mut tokens, Token::new(TokenKind::ClosingSquareBracket, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '|' => {
-                    tokens.push(Token::new(TokenKind::VerticalBar, c.to_string(), self.current_row, self.current_column));
+                '@' => {
+                    self.push(&mut tokens, Token::new(TokenKind::At, c.to_string(), self.current_row, self.current_column));
                     self.next();
                 }
-                '\'' => {
+                '$' => {
+                    self.push(&mut tokens, Token::new(TokenKind::Dollar, c.to_string(), self.current_row, self.current_column));
                     self.next();
-                    tokens.push(Token::new(TokenKind::CharLiteral, self.current_char().to_string(), self.current_row, self.current_column));
-                    self.currently_at += 2;
-                    self.current_column += 2;
                 }
-                '\"' => {
-                    let row = self.current_row;
-                    let column = self.current_column;
-                    self.next();
-                    let mut buffer: String = String::new();
-                    while self.current_char() != '\"' && self.source.len() - 1 > self.currently_at {
-                        buffer.push(self.current_char());
-                        self.next();
+                '\'' => {
+                    let (raw, cooked) = self.lex_quoted('\'');
+                    let mut token = Token::new(TokenKind::CharLiteral, raw, start.row, start.column);
+                    if let Some(cooked) = cooked {
+                        let mut chars = cooked.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(c), None) => token.value = Some(TokenValue::Char(c)),
+                            _ => {
+                                self.error(&start, "A Char literal must contain exactly one character".to_string());
+                            }
+                        }
                     }
-                    tokens.push(Token::new(TokenKind::StringLiteral, buffer, row, column));
-                    self.next();
+                    self.push(&mut tokens, token);
+                }
+                '\"' => {
+                    let (raw, cooked) = self.lex_quoted('\"');
+                    let mut token = Token::new(TokenKind::StringLiteral, raw, start.row, start.column);
+                    token.value = cooked.map(TokenValue::Str);
+                    self.push(&mut tokens, token);
                 }
                 _ => {
-                    let row = self.current_row;
-                    let column = self.current_column;
-                    if c.is_alphabetic() {
-                        // Creating a buffer and writing a first character to it
-                        let mut buffer = String::new();
-                        buffer.push(c);
-                        self.next();
-
-                        if self.source.get(self.currently_at) != None {
+                    if is_identifier_start(c) {
+                        let buffer = self.scan_identifier();
 
-                            // Writing everything that is alphabetic to the buffer
-                            while self.current_char().is_alphabetic() && self.source.len() - 1 > self.currently_at {
-                                buffer.push(self.current_char());
-                                self.next();
-                            }
-
-                            // Then matching for reserved words. If it's not reserved, then it's an identifier
-                            let kind: TokenKind = match buffer.as_str() {
-                                "as" => TokenKind::As,
-                                "and" => TokenKind::And,
-                                "break" => TokenKind::Break,
-                                "case" => TokenKind::Case,
-                                "catch" => TokenKind::Catch,
-                                "class" => TokenKind::Class,
-                                "const" => TokenKind::Const,
-                                "continue" => TokenKind::Continue,
-                                "default" => TokenKind::Default,
-                                "do" => TokenKind::Do,
-                                "else" => TokenKind::Else,
-                                "enum" => TokenKind::Enum,
-                                "extends" => TokenKind::Extends,
-                                "finally" => TokenKind::Finally,
-                                "for" => TokenKind::For,
-                                "function" => TokenKind::Function,
-                                "has" => TokenKind::Has,
-                                "hidden" => TokenKind::Hidden,
-                                "if" => TokenKind::If,
-                                "instanceof" => TokenKind::InstanceOf,
-                                "import" => TokenKind::Import,
-                                "me" => TokenKind::Me,
-                                "module" => TokenKind::Module,
-                                "new" => TokenKind::New,
-                                "null" => TokenKind::Null,
-                                "NaN" => TokenKind::Nan,
-                                "private" => TokenKind::Private,
-                                "protected" => TokenKind::Protected,
-                                "public" => TokenKind::Public,
-                                "or" => TokenKind::Or,
-                                "return" => TokenKind::Return,
-                                "self" => TokenKind::Self_,
-                                "static" => TokenKind::Static,
-                                "switch" => TokenKind::Switch,
-                                "throw" => TokenKind::Throw,
-                                "try" => TokenKind::Try,
-                                "using" => TokenKind::Using,
-                                "var" => TokenKind::Var,
-                                "while" => TokenKind::While,
-                                _ => TokenKind::Identifier,
-                            };
-                            tokens.push(Token::new(kind, buffer, row, column))
-                        } else {
-                            bail!("Unexpected end of file");
-                        }
-                    } else if c.is_numeric() {
-                        // Creating a buffer and writing a first character to it
-                        let mut buffer = String::new();
-                        buffer.push(c);
-                        let mut token_type = TokenKind::IntLiteral;
-                        let mut got_alphabetic = false;
-
-                        // Writing everything that is numeric or ./l/d to the buffer
-                        while self.current_char().is_alphanumeric() || self.current_char() == '.' || self.current_char() == 'l' || self.current_char() == 'd' {
-                            match self.current_char() {
-                                '.' => {
-                                    token_type = TokenKind::FloatLiteral;
-                                }
-                                'l' => {
-                                    if got_alphabetic == false {
-                                        if token_type == TokenKind::FloatLiteral {
-                                            bail!(format!("Bad token at {}:{}: A Float can't have 'l' at the end", self.current_row, self.current_column));
-                                        }
-                                    } else {
-                                        bail!(format!("Bad token at {}:{}: You can't have multiple number decorators", self.current_row, self.current_column));
-                                    }
-                                    got_alphabetic = true;
-                                    token_type = TokenKind::LongLiteral;
-                                }
-                                'd' => {
-                                    if got_alphabetic == false {
-                                        if token_type == TokenKind::FloatLiteral {
-                                            bail!(format!("Bad token at {}:{}: A Float can't have 'd' at the end", self.current_row, self.current_column));
-                                        }
-                                    } else {
-                                        bail!(format!("Bad token at {}:{}: Multiple number decorators are forbidden", self.current_row, self.current_column));
-                                    }
-                                    got_alphabetic = true;
-                                    token_type = TokenKind::DoubleLiteral;
-                                }
-                                _ => {}
-                            }
-                            buffer.push(self.current_char());
-                            self.next();
-                        }
-                        tokens.push(Token::new(token_type, buffer, row, column));
+                        // Then matching for reserved words. If it's not reserved, then it's an identifier
+                        let kind: TokenKind = match buffer.as_str() {
+                            "as" => TokenKind::As,
+                            "and" => TokenKind::And,
+                            "break" => TokenKind::Break,
+                            "case" => TokenKind::Case,
+                            "catch" => TokenKind::Catch,
+                            "class" => TokenKind::Class,
+                            "const" => TokenKind::Const,
+                            "continue" => TokenKind::Continue,
+                            "default" => TokenKind::Default,
+                            "do" => TokenKind::Do,
+                            "else" => TokenKind::Else,
+                            "enum" => TokenKind::Enum,
+                            "extends" => TokenKind::Extends,
+                            "false" => TokenKind::BoolLiteral,
+                            "finally" => TokenKind::Finally,
+                            "for" => TokenKind::For,
+                            "function" => TokenKind::Function,
+                            "has" => TokenKind::Has,
+                            "hidden" => TokenKind::Hidden,
+                            "if" => TokenKind::If,
+                            "instanceof" => TokenKind::InstanceOf,
+                            "import" => TokenKind::Import,
+                            "me" => TokenKind::Me,
+                            "module" => TokenKind::Module,
+                            "new" => TokenKind::New,
+                            "null" => TokenKind::Null,
+                            "NaN" => TokenKind::Nan,
+                            "private" => TokenKind::Private,
+                            "protected" => TokenKind::Protected,
+                            "public" => TokenKind::Public,
+                            "or" => TokenKind::Or,
+                            "return" => TokenKind::Return,
+                            "self" => TokenKind::Self_,
+                            "static" => TokenKind::Static,
+                            "switch" => TokenKind::Switch,
+                            "throw" => TokenKind::Throw,
+                            "true" => TokenKind::BoolLiteral,
+                            "try" => TokenKind::Try,
+                            "using" => TokenKind::Using,
+                            "var" => TokenKind::Var,
+                            "while" => TokenKind::While,
+                            _ => TokenKind::Identifier,
+                        };
+                        self.push(&mut tokens, Token::new(kind, buffer, start.row, start.column));
+                    } else if c.is_ascii_digit() {
+                        self.lex_number(&mut tokens);
+                    } else if c.is_whitespace() {
+                        let trivia = self.lex_whitespace();
+                        self.push_trivia(&mut tokens, trivia);
                     } else {
                         self.next();
+                        self.error(&start, format!("Unknown character '{}'", c.escape_default()));
+                        self.push(&mut tokens, Token::new(TokenKind::Error, c.to_string(), start.row, start.column));
                     }
                 }
             }
+
+            // Every branch produces at most one token
+            if tokens.len() > token_count {
+                let span = self.span_from(&start);
+                if let Some(token) = tokens.last_mut() {
+                    token.span = span;
+                }
+            }
         }
-        Ok(tokens)
+
+        // Comments at the end of the file go to the end of file token
+        let start = self.position();
+        let mut eof = Token::new(TokenKind::Eof, String::new(), start.row, start.column);
+        eof.span = self.span_from(&start);
+        self.push(&mut tokens, eof);
+
+        (tokens, std::mem::take(&mut self.errors))
     }
+}
 
-    fn current_char(&self) -> char {
-        *self.source.get(self.currently_at).unwrap()
+/// Computes the value of an already validated numeric literal.
+/// `digits` must not contain a radix prefix or a suffix. Hex and
+/// octal literals may use the sign bit, like `0xFFFFFFFF` for -1,
+/// but decimal ones have to fit without it.
+fn parse_number_value(kind: &TokenKind, digits: &str, radix: u32) -> std::result::Result<TokenValue, String> {
+    match kind {
+        TokenKind::FloatLiteral => match digits.parse::<f32>() {
+            Ok(v) if v.is_finite() => Ok(TokenValue::Float(v)),
+            Ok(_) => Err("Float literal is out of range".to_string()),
+            Err(_) => Err(format!("Invalid Float literal '{}'", digits)),
+        },
+        TokenKind::DoubleLiteral => match digits.parse::<f64>() {
+            Ok(v) if v.is_finite() => Ok(TokenValue::Double(v)),
+            Ok(_) => Err("Double literal is out of range".to_string()),
+            Err(_) => Err(format!("Invalid Double literal '{}'", digits)),
+        },
+        TokenKind::LongLiteral => match u64::from_str_radix(digits, radix) {
+            Ok(v) if radix != 10 || v <= i64::MAX as u64 => Ok(TokenValue::Long(v as i64)),
+            _ => Err("Integer literal is too large for a Long".to_string()),
+        },
+        _ => match u64::from_str_radix(digits, radix) {
+            Ok(v) if v <= i32::MAX as u64 || (radix != 10 && v <= u32::MAX as u64) => Ok(TokenValue::Int(v as u32 as i32)),
+            _ => Err("Integer literal is too large, add an 'l' to make it a Long".to_string()),
+        }
     }
 }
+
+/// A position inside of the source
+#[derive(Debug)]
+struct Position {
+    row: u64,
+    column: u64,
+    /// Index of the character
+    index: usize,
+    /// Offset in bytes
+    offset: usize
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
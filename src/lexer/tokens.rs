@@ -1,18 +1,85 @@
-#[derive(Debug, Clone, PartialEq)]
+use crate::span::Span;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Token {
     pub kind: TokenKind,
     pub literal: String,
     pub row: u64,
-    pub column: u64
+    pub column: u64,
+    /// Location of the whole token, including quotes and such
+    pub span: Span,
+    /// Parsed value of a literal. It's `None` for
+    /// everything else and for malformed literals.
+    pub value: Option<TokenValue>,
+    /// Trivia between the previous line break (or the
+    /// previous token's trailing trivia) and this token
+    pub leading_trivia: Vec<Trivia>,
+    /// Trivia after this token up to the end of the line,
+    /// not including the line break itself
+    pub trailing_trivia: Vec<Trivia>
 }
 
 impl Token {
     pub fn new(kind: TokenKind, literal: String, row: u64, column: u64) -> Self {
-        Self { kind, literal, row, column }
+        Self { kind, literal, row, column, span: Span::default(), value: None, leading_trivia: Vec::new(), trailing_trivia: Vec::new() }
+    }
+
+    /// Describes the token for error messages
+    pub fn describe(&self) -> String {
+        match self.kind {
+            TokenKind::Eof => "end of file".to_string(),
+            _ => format!("'{}'", self.literal),
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum TokenValue {
+    /// A 32-bit Number. Hexadecimal literals wrap around,
+    /// so `0xFFFFFFFF` is `-1`.
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Char(char),
+    /// A String with all escape sequences decoded
+    Str(String)
+}
+
+/// Source text that doesn't affect the meaning
+/// of the program, but that is still worth keeping
+/// around, like doc comments, suppression directives
+/// or whitespace a formatter needs to look at.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    /// Full text of the trivia, including comment delimiters
+    pub text: String,
+    pub row: u64,
+    pub column: u64,
+    pub span: Span
+}
+
+impl Trivia {
+    pub fn new(kind: TriviaKind, text: String, row: u64, column: u64) -> Self {
+        Self { kind, text, row, column, span: Span::default() }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum TriviaKind {
+    /// Spaces, tabs and other whitespace except line breaks
+    Whitespace,
+    /// This one: \n, or \r\n
+    Newline,
+    /// This one: // ...
+    LineComment,
+    /// This one: /* ... */
+    BlockComment
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub enum TokenKind {
     As,
     And,
@@ -61,6 +128,8 @@ pub enum TokenKind {
     DoubleLiteral,
     Null,
     CharLiteral,
+    /// This one: :name. The literal doesn't include the colon.
+    SymbolLiteral,
     Nan,
     New,
     Identifier,
@@ -88,5 +157,55 @@ pub enum TokenKind {
     GreaterThan,
     /// This one: ^
     Caret,
-    VerticalBar
+    VerticalBar,
+    /// This one: ==
+    Equals,
+    /// This one: !=
+    NotEquals,
+    /// This one: <=
+    LessThanEquals,
+    /// This one: >=
+    GreaterThanEquals,
+    /// This one: &&
+    DoubleAmpersand,
+    /// This one: ||
+    DoubleVerticalBar,
+    /// This one: <<
+    LeftShift,
+    /// This one: >>
+    RightShift,
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
+    PercentAssign,
+    AmpersandAssign,
+    VerticalBarAssign,
+    CaretAssign,
+    LeftShiftAssign,
+    RightShiftAssign,
+    /// This one: ++
+    Increment,
+    /// This one: --
+    Decrement,
+    /// This one: ?:
+    Elvis,
+    Dot,
+    Colon,
+    /// This one: [
+    OpeningSquareBracket,
+    /// This one: ]
+    ClosingSquareBracket,
+    /// This one: ?
+    QuestionMark,
+    /// This one: @
+    At,
+    /// This one: $, used to refer to the global module
+    Dollar,
+    /// This one: =>
+    Arrow,
+    /// Something that couldn't be lexed, like an unknown character
+    Error,
+    /// Always the last token. Holds comments at the end of the file.
+    Eof
 }
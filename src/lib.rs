@@ -0,0 +1,56 @@
+//! A static analyser for Monkey C language.
+//!
+//! `lex`, `parse` and `lint` are the entry points. The modules
+//! below them give access to the tokens, the syntax trees and
+//! the formatter for tools that need more than that.
+
+pub mod config;
+pub mod cst;
+mod diagnostic;
+pub mod fmt;
+pub mod lexer;
+pub mod parser;
+pub mod span;
+#[cfg(test)]
+mod tests;
+
+pub use crate::config::Config;
+pub use crate::diagnostic::{Diagnostic, DiagnosticKind};
+
+use crate::lexer::tokens::Token;
+use crate::lexer::MonkeyCLexer;
+use crate::parser::ast::MonkeyCStatement;
+use crate::parser::MonkeyCParser;
+use std::path::PathBuf;
+
+/// Splits `source` into tokens. The list always ends with
+/// an end of file token, even if there were errors.
+pub fn lex(source: &str) -> (Vec<Token>, Vec<Diagnostic>) {
+    let (tokens, errors) = MonkeyCLexer::new(source.chars().collect()).lex();
+    (tokens, errors.into_iter().map(Diagnostic::from).collect())
+}
+
+/// Builds a syntax tree out of tokens returned by `lex`.
+/// Malformed parts of the code become `Error` nodes.
+pub fn parse_tokens(tokens: Vec<Token>) -> (Vec<MonkeyCStatement>, Vec<Diagnostic>) {
+    // Diagnostics don't mention the file, so the parser doesn't need it
+    let (statements, errors) = MonkeyCParser::new(tokens, PathBuf::new()).parse();
+    (statements, errors.into_iter().map(Diagnostic::from).collect())
+}
+
+/// Lexes and parses `source`. Lexing errors go
+/// before parsing errors in the diagnostics.
+pub fn parse(source: &str) -> (Vec<MonkeyCStatement>, Vec<Diagnostic>) {
+    let (tokens, mut diagnostics) = lex(source);
+    let (statements, errors) = parse_tokens(tokens);
+    diagnostics.extend(errors);
+    (statements, diagnostics)
+}
+
+/// Finds every problem in `source`. So far these are
+/// lexing and parsing errors only.
+pub fn lint(source: &str, config: &Config) -> Vec<Diagnostic> {
+    // None of the checks can be configured yet
+    let _ = config;
+    parse(source).1
+}
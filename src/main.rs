@@ -1,88 +1,214 @@
-mod lexer;
-mod parser;
-#[cfg(test)]
-mod tests;
-
-use crate::lexer::MonkeyCLexer;
 use anyhow::Context;
 use anyhow::Result;
-use clap::{App, Arg};
-use std::fs;
-use std::path::PathBuf;
-use crate::parser::MonkeyCParser;
-use std::fs::File;
-use std::io::{BufReader, BufRead};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use colored::Colorize;
+use mclint::span::{LineIndex, Span};
+use mclint::fmt::FormatError;
+use mclint::{cst, fmt, Config, Diagnostic};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 
 fn main() -> Result<()> {
     let matches = App::new("MCLint")
         .version("0.1.0")
         .author("GGorAA <yegor_yakovenko@icloud.com>")
         .about("A linter for Monkey C language.")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::with_name("INPUT")
                 .help("An input file to lint")
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("emit")
+                .long("emit")
+                .help("Prints the tokens, the syntax tree or the lossless bracket tree of the file as JSON")
+                .takes_value(true)
+                .possible_values(&["tokens", "ast", "cst"]),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .help("Reads settings from this file instead of looking for mclint.toml")
+                .takes_value(true)
+                .global(true),
+        )
+        .subcommand(
+            SubCommand::with_name("fmt")
+                .about("Formats files in place, using the [format] settings of mclint.toml")
+                .arg(
+                    Arg::with_name("FILES")
+                        .help("Files to format")
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("Doesn't change the files, and fails if any of them isn't formatted"),
+                )
+                .arg(
+                    Arg::with_name("diff")
+                        .long("diff")
+                        .help("Doesn't change the files, and prints how they would change instead"),
+                ),
+        )
         .get_matches();
 
+    if let Some(matches) = matches.subcommand_matches("fmt") {
+        return format_files(matches);
+    }
+
     let file_path = PathBuf::from(matches.value_of("INPUT").unwrap());
     let file_contents = fs::read_to_string(file_path.clone())
         .with_context(|| format!("Failed to read contents of {}", file_path.display()))?;
 
-    let mut lexer = MonkeyCLexer::new(file_contents.chars().collect());
-    let tokens = lexer.lex().with_context(|| format!("Failed to tokenize {:?}", file_path.clone()))?;
-
-    let mut parser = MonkeyCParser::new(tokens, file_path.clone());
-    match parser.parse() {
-        Ok(data) => {
-            println!("{:?}", data);
+    match matches.value_of("emit") {
+        Some("tokens") => {
+            let (tokens, errors) = mclint::lex(&file_contents);
+            return print_json(json!({ "tokens": tokens, "errors": errors }));
+        }
+        Some("cst") => {
+            let (tokens, errors) = mclint::lex(&file_contents);
+            return print_json(json!({ "root": cst::build(&file_contents, &tokens), "errors": errors }));
         }
-        Err(errors) => {
-            for error in errors {
-                println!("{} {}", "|".bright_red(), error.full_msg.as_str().bright_red());
-
-                // Getting a line specified inside of error struct
-                let file = File::open(file_path.clone()).unwrap();
-                let reader = BufReader::new(file);
-                for (index, line) in reader.lines().enumerate() {
-                    if index + 1 == error.at.0 as usize {
-                        print!("{}", "| ".cyan());
-                        println!("{}", String::from(line.unwrap()).cyan());
-                        break;
-                    }
-                }
-
-                print!("{}", "| ".cyan());
-
-                // Adding an underline
-                for _ in 0..error.at.1 - 1 {
-                    print!(" ");
-                }
-                for _ in 0..error.literal_len {
-                    print!("{}", "~".cyan())
-                }
-                println!();
-
-                print!("{}", "| ".cyan());
-
-                // Adding a small pointer
-                for _ in 0..error.at.1 - 1 {
-                    print!(" ");
-                }
-                println!("{}", "| ".cyan());
-
-                print!("{}", "| ".cyan());
-
-                for _ in 0..error.at.1 - 1 {
-                    print!(" ");
-                }
-                println!("{}\n", "| here".cyan());
+        Some("ast") => {
+            let (statements, errors) = mclint::parse(&file_contents);
+            return print_json(json!({ "statements": statements, "errors": errors }));
+        }
+        _ => {}
+    }
+
+    let config = read_config(&matches)?;
+    print_diagnostics(&file_path, &file_contents, &mclint::lint(&file_contents, &config));
+
+    Ok(())
+}
+
+/// Reads the file given with `--config`, or looks for one
+fn read_config(matches: &ArgMatches) -> Result<Config> {
+    match matches.value_of("config") {
+        Some(path) => Config::read(Path::new(path)),
+        None => Config::find(),
+    }
+}
+
+/// Prints every diagnostic found in `file_contents`
+fn print_diagnostics(file_path: &Path, file_contents: &str, diagnostics: &[Diagnostic]) {
+    let line_index = LineIndex::new(file_contents);
+    for diagnostic in diagnostics {
+        print_error(&line_index, diagnostic.at, diagnostic.span, &diagnostic.full_message(file_path));
+    }
+}
+
+/// Formats every file in place. With `--check` or `--diff`,
+/// the files are only checked, and the process fails if
+/// `--check` is set and any of them isn't formatted.
+fn format_files(matches: &ArgMatches) -> Result<()> {
+    let config = read_config(matches)?;
+    let check = matches.is_present("check");
+    let show_diff = matches.is_present("diff");
+
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for file in matches.values_of("FILES").unwrap() {
+        let file_path = PathBuf::from(file);
+        let file_contents = fs::read_to_string(&file_path)
+            .with_context(|| format!("Failed to read contents of {}", file_path.display()))?;
+
+        let formatted = match fmt::format_source(&file_contents, &config.format) {
+            Ok(formatted) => formatted,
+            Err(FormatError::Syntax(errors)) => {
+                print_diagnostics(&file_path, &file_contents, &errors);
+                failed += 1;
+                continue;
             }
+            Err(FormatError::ChangesMeaning) => {
+                println!("{} {}", "|".bright_red(),
+                         format!("Formatting {} would change what the code does, so it was left alone",
+                                 file_path.display()).bright_red());
+                failed += 1;
+                continue;
+            }
+        };
+        if formatted == file_contents {
+            continue;
+        }
+
+        if show_diff {
+            print!("{}", fmt::diff(&file_path, &file_contents, &formatted));
+        } else if check {
+            println!("{} is not formatted", file_path.display());
+        } else {
+            fs::write(&file_path, formatted)
+                .with_context(|| format!("Failed to write {}", file_path.display()))?;
         }
+        unformatted += 1;
     }
 
+    if failed > 0 {
+        anyhow::bail!("{} file(s) couldn't be formatted", failed);
+    }
+    if check && unformatted > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Prints `value` for other tools to read. Lexing and
+/// parsing errors are a part of it, so nothing else
+/// is printed.
+fn print_json(value: Value) -> Result<()> {
+    let mut stdout = io::stdout();
+    serde_json::to_writer_pretty(&mut stdout, &value)?;
+    writeln!(stdout)?;
     Ok(())
 }
 
+/// Prints an error message together with the line
+/// it points to, underlining the erroneous part
+fn print_error(line_index: &LineIndex, at: (u64, u64), span: Span, full_msg: &str) {
+    println!("{} {}", "|".bright_red(), full_msg.bright_red());
+
+    let (line, column) = at;
+    let line_text = line_index.line_text(line);
+    print!("{}", "| ".cyan());
+    println!("{}", line_text.cyan());
+
+    // Spans that go past the end of the line are only underlined up to it
+    let end_column = match line_index.line_col(span.end) {
+        (end_line, end_column) if end_line == line => end_column,
+        _ => line_text.chars().count() as u64 + 1,
+    };
+    let underline_len = end_column.saturating_sub(column).max(1);
+
+    print!("{}", "| ".cyan());
+
+    // Adding an underline
+    for _ in 0..column - 1 {
+        print!(" ");
+    }
+    for _ in 0..underline_len {
+        print!("{}", "~".cyan())
+    }
+    println!();
+
+    print!("{}", "| ".cyan());
+
+    // Adding a small pointer
+    for _ in 0..column - 1 {
+        print!(" ");
+    }
+    println!("{}", "| ".cyan());
+
+    print!("{}", "| ".cyan());
+
+    for _ in 0..column - 1 {
+        print!(" ");
+    }
+    println!("{}\n", "| here".cyan());
+}
@@ -1,62 +1,675 @@
-#[derive(Debug, PartialEq)]
+use crate::span::Span;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
 pub enum MonkeyCStatement {
+    /// # Example
+    /// ```text
+    /// var fooVar;
+    /// var barVar as Number = 1, bazVar = barVar + 1;
+    /// (:debug) const QUX = "qux";
+    /// ```
     VariableDeclaration {
-        name: String,
-        default_val: MonkeyCExpression,
-        var_type: Option<String>,
-        is_const: bool
+        declarators: Vec<MonkeyCVariableDeclarator>,
+        is_const: bool,
+        annotations: Vec<MonkeyCAnnotation>,
+        modifiers: Vec<MonkeyCModifier>,
+        span: Span
     },
+    /// # Example
+    /// ```text
+    /// (:background)
+    /// class Foo extends Toybox.Application.AppBase {
+    ///     private var bar = 1;
+    /// }
+    /// ```
     ClassDeclaration {
         name: String,
+        /// Qualified name of the superclass, like `Toybox.Application.AppBase`
         extends: Option<String>,
+        annotations: Vec<MonkeyCAnnotation>,
+        modifiers: Vec<MonkeyCModifier>,
         children: Vec<MonkeyCStatement>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// (:test)
+    /// static function add(a as Number, b as Number) as Number {
+    ///     return a + b;
+    /// }
+    /// ```
+    FunctionDeclaration {
+        name: String,
+        params: Vec<MonkeyCParameter>,
+        return_type: Option<MonkeyCTypeExpression>,
+        annotations: Vec<MonkeyCAnnotation>,
+        modifiers: Vec<MonkeyCModifier>,
+        /// Always a `Block`
+        body: Box<MonkeyCStatement>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// (:glance)
+    /// module Utils {
+    ///     const VERSION = 2;
+    /// }
+    /// ```
+    ModuleDeclaration {
+        name: String,
+        annotations: Vec<MonkeyCAnnotation>,
+        /// Modules can't have any, but they are
+        /// kept so that errors can point at them
+        modifiers: Vec<MonkeyCModifier>,
+        children: Vec<MonkeyCStatement>,
+        span: Span
+    },
+    /// # Examples
+    /// ```text
+    /// using Toybox.Graphics;
+    /// using Toybox.Graphics as Gfx;
+    /// ```
+    Using {
+        /// Qualified name of the module
+        path: String,
+        alias: Option<String>,
+        annotations: Vec<MonkeyCAnnotation>,
+        /// Always an error, like for modules
+        modifiers: Vec<MonkeyCModifier>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// import Toybox.Lang;
+    /// ```
+    Import {
+        /// Qualified name of the module
+        path: String,
+        annotations: Vec<MonkeyCAnnotation>,
+        /// Always an error, like for modules
+        modifiers: Vec<MonkeyCModifier>,
+        span: Span
+    },
+    /// # Examples
+    /// ```text
+    /// enum {
+    ///     STATE_IDLE,
+    ///     STATE_RUNNING = 5
+    /// }
+    /// enum Direction { UP, DOWN }
+    /// ```
+    EnumDeclaration {
+        name: Option<String>,
+        members: Vec<MonkeyCEnumMember>,
+        annotations: Vec<MonkeyCAnnotation>,
+        modifiers: Vec<MonkeyCModifier>,
+        span: Span
+    },
+    /// An expression evaluated for its side effects.
+    /// # Examples
+    /// ```text
+    /// count += 1;
+    /// i++;
+    /// view.requestUpdate();
+    /// ```
+    ExpressionStatement {
+        expression: MonkeyCExpression,
+        span: Span
+    },
+    /// A lone `;`, like the body of `while (poll());`
+    Empty {
+        span: Span
+    },
+    /// Something that couldn't be parsed. The parser skips
+    /// to a point where it can continue, and puts this in
+    /// place of everything it skipped.
+    Error {
+        span: Span
+    },
+    /// Statements between `{` and `}`
+    Block {
+        children: Vec<MonkeyCStatement>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// if (a > b) {
+    ///     return a;
+    /// } else if (a < b) {
+    ///     return b;
+    /// }
+    /// ```
+    If {
+        condition: MonkeyCExpression,
+        then_branch: Box<MonkeyCStatement>,
+        else_branch: Option<Box<MonkeyCStatement>>,
+        span: Span
+    },
+    While {
+        condition: MonkeyCExpression,
+        body: Box<MonkeyCStatement>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// do {
+    ///     i++;
+    /// } while (i < 10);
+    /// ```
+    DoWhile {
+        body: Box<MonkeyCStatement>,
+        condition: MonkeyCExpression,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// for (var i = 0; i < 10; i++) {
+    ///     sum += i;
+    /// }
+    /// ```
+    For {
+        /// A variable declaration or an expression statement
+        init: Option<Box<MonkeyCStatement>>,
+        condition: Option<MonkeyCExpression>,
+        update: Option<MonkeyCExpression>,
+        body: Box<MonkeyCStatement>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// switch (key) {
+    ///     case :up:
+    ///         moveUp();
+    ///         break;
+    ///     default:
+    ///         return false;
+    /// }
+    /// ```
+    Switch {
+        value: MonkeyCExpression,
+        cases: Vec<MonkeyCSwitchCase>,
+        span: Span
+    },
+    Break {
+        span: Span
+    },
+    Continue {
+        span: Span
+    },
+    Return {
+        value: Option<MonkeyCExpression>,
+        span: Span
+    },
+    Throw {
+        value: MonkeyCExpression,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// try {
+    ///     connect();
+    /// } catch (e instanceof Lang.Exception) {
+    ///     log(e);
+    /// } finally {
+    ///     cleanUp();
+    /// }
+    /// ```
+    Try {
+        body: Box<MonkeyCStatement>,
+        catches: Vec<MonkeyCCatch>,
+        finally: Option<Box<MonkeyCStatement>>,
+        span: Span
     }
     // Others will be added in later
 }
 
-#[derive(Debug, PartialEq)]
+impl MonkeyCStatement {
+    /// Location of the whole statement
+    pub fn span(&self) -> Span {
+        match self {
+            MonkeyCStatement::VariableDeclaration { span, .. } => *span,
+            MonkeyCStatement::ClassDeclaration { span, .. } => *span,
+            MonkeyCStatement::FunctionDeclaration { span, .. } => *span,
+            MonkeyCStatement::ModuleDeclaration { span, .. } => *span,
+            MonkeyCStatement::Using { span, .. } => *span,
+            MonkeyCStatement::Import { span, .. } => *span,
+            MonkeyCStatement::EnumDeclaration { span, .. } => *span,
+            MonkeyCStatement::ExpressionStatement { span, .. } => *span,
+            MonkeyCStatement::Empty { span } => *span,
+            MonkeyCStatement::Error { span } => *span,
+            MonkeyCStatement::Block { span, .. } => *span,
+            MonkeyCStatement::If { span, .. } => *span,
+            MonkeyCStatement::While { span, .. } => *span,
+            MonkeyCStatement::DoWhile { span, .. } => *span,
+            MonkeyCStatement::For { span, .. } => *span,
+            MonkeyCStatement::Switch { span, .. } => *span,
+            MonkeyCStatement::Break { span } => *span,
+            MonkeyCStatement::Continue { span } => *span,
+            MonkeyCStatement::Return { span, .. } => *span,
+            MonkeyCStatement::Throw { span, .. } => *span,
+            MonkeyCStatement::Try { span, .. } => *span,
+        }
+    }
+}
+
+/// A `case` or `default` label and the
+/// statements up to the next label
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonkeyCSwitchCase {
+    /// `None` for `default`
+    pub value: Option<MonkeyCExpression>,
+    pub children: Vec<MonkeyCStatement>,
+    pub span: Span
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonkeyCCatch {
+    /// Name the exception is bound to
+    pub binding: String,
+    /// Qualified name after `instanceof`, if there is one
+    pub class: Option<String>,
+    /// Always a `Block`
+    pub body: Box<MonkeyCStatement>,
+    pub span: Span
+}
+
+/// An annotation like `(:background)` or `(:typecheck(false))`.
+/// Several of them can share the same brackets, as in
+/// `(:debug, :test)`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonkeyCAnnotation {
+    /// Name of the annotation, without the colon
+    pub name: String,
+    pub args: Vec<MonkeyCExpression>,
+    pub span: Span
+}
+
+/// A single variable of a declaration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonkeyCVariableDeclarator {
+    pub name: String,
+    pub var_type: Option<MonkeyCTypeExpression>,
+    /// Always present for constants
+    pub default_val: Option<MonkeyCExpression>,
+    pub span: Span
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonkeyCEnumMember {
+    pub name: String,
+    /// Explicit value, if there is one
+    pub value: Option<MonkeyCExpression>,
+    pub span: Span
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonkeyCParameter {
+    pub name: String,
+    pub param_type: Option<MonkeyCTypeExpression>,
+    pub span: Span
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MonkeyCModifier {
+    Static,
+    Hidden,
+    Private,
+    Protected,
+    Public
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
 pub enum MonkeyCExpression {
     /// "Simple" assignment
     /// # Example
-    /// ```
+    /// ```text
     /// var myVar = "simple string"
     /// ```
-    Simple(String),
+    Simple {
+        /// Literal as written, without quotes or the colon of a symbol
+        value: String,
+        kind: MonkeyCLiteralKind,
+        span: Span
+    },
     /// Reference to other variables/functions/classes/etc
     ///
     /// # Examples
-    /// ```
+    /// ```text
     /// var fooVar = fooFunc();
     /// var barVar = new BarClass();
     /// var bazVar = otherBazVar;
     /// ```
-    Reference(String),
+    Reference {
+        name: String,
+        span: Span
+    },
     /// Mathematical equations.
     /// # Examples
-    /// ```
+    /// ```text
     /// var fooVar = 1 + 2;
     /// var barVar = fooVar * 2;
     /// var bazVar = barVar / 2;
     /// ```
-    Mathematical(Box<MonkeyCExpression>, MonkeyCExprMathOperation, Box<MonkeyCExpression>),
+    Mathematical {
+        lhs: Box<MonkeyCExpression>,
+        op: MonkeyCExprMathOperation,
+        rhs: Box<MonkeyCExpression>,
+        span: Span
+    },
     /// Binary operations.
     /// # Examples
-    /// ```
+    /// ```text
     /// var fooVar = 1 <= 2;
     /// if (fooVar) { // Should be true, otherwise the language broke
     ///     // Do smth
     /// }
     /// ```
-    Binary(Box<MonkeyCExpression>, MonkeyCExprBinaryOperation, Box<MonkeyCExpression>),
+    Binary {
+        lhs: Box<MonkeyCExpression>,
+        op: MonkeyCExprBinaryOperation,
+        rhs: Box<MonkeyCExpression>,
+        span: Span
+    },
     /// Bitwise operations.
     /// # Examples
-    /// ```
+    /// ```text
     /// var fooVar = 0x111 << 0x001;
     /// ```
-    Bitwise(Box<MonkeyCExpression>, MonkeyCExprBitwiseOperation, Box<MonkeyCExpression>)
+    Bitwise {
+        lhs: Box<MonkeyCExpression>,
+        op: MonkeyCExprBitwiseOperation,
+        rhs: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// Logical operations, written either with
+    /// symbols or with words.
+    /// # Examples
+    /// ```text
+    /// var fooVar = isReady && !isPaused;
+    /// var barVar = fooVar or hasData;
+    /// ```
+    Logical {
+        lhs: Box<MonkeyCExpression>,
+        op: MonkeyCExprLogicalOperation,
+        rhs: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// Operations with a single operand.
+    /// # Examples
+    /// ```text
+    /// var fooVar = -barVar;
+    /// var bazVar = !fooVar;
+    /// ```
+    Unary {
+        op: MonkeyCExprUnaryOperation,
+        operand: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// Increments and decrements.
+    /// # Examples
+    /// ```text
+    /// i++;
+    /// --j;
+    /// ```
+    Update {
+        op: MonkeyCExprUpdateOperation,
+        operand: Box<MonkeyCExpression>,
+        is_prefix: bool,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var fooVar = barVar > 0 ? barVar : 0;
+    /// ```
+    Ternary {
+        condition: Box<MonkeyCExpression>,
+        if_true: Box<MonkeyCExpression>,
+        if_false: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// Takes the right side if the left one is null.
+    /// # Example
+    /// ```text
+    /// var fooVar = barVar ?: "default";
+    /// ```
+    Elvis {
+        lhs: Box<MonkeyCExpression>,
+        rhs: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// Only appears in expression statements and in the
+    /// head of a `for` loop. The target is always a
+    /// `Reference`, a `Member` or an `Index`.
+    /// # Examples
+    /// ```text
+    /// fooVar = 1;
+    /// self.items[i] += 2;
+    /// ```
+    Assignment {
+        target: Box<MonkeyCExpression>,
+        op: MonkeyCExprAssignmentOperation,
+        value: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var fooVar = barDict["foo"] as Number;
+    /// ```
+    Cast {
+        value: Box<MonkeyCExpression>,
+        target_type: MonkeyCTypeExpression,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var isString = fooVar instanceof Lang.String;
+    /// ```
+    InstanceOf {
+        value: Box<MonkeyCExpression>,
+        class: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var hasVibe = Attention has :vibrate;
+    /// ```
+    Has {
+        value: Box<MonkeyCExpression>,
+        member: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var fooVar = Toybox.Graphics;
+    /// ```
+    Member {
+        object: Box<MonkeyCExpression>,
+        name: String,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var fooVar = barArray[0];
+    /// ```
+    Index {
+        object: Box<MonkeyCExpression>,
+        index: Box<MonkeyCExpression>,
+        span: Span
+    },
+    /// Function and method calls, method references included.
+    /// # Examples
+    /// ```text
+    /// var fooVar = fooFunc(1, 2);
+    /// var callback = method(:onTimer);
+    /// ```
+    Call {
+        callee: Box<MonkeyCExpression>,
+        args: Vec<MonkeyCExpression>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var timer = new Timer.Timer();
+    /// ```
+    New {
+        class: Box<MonkeyCExpression>,
+        args: Vec<MonkeyCExpression>,
+        span: Span
+    },
+    /// Creates an array of a given size, filled with nulls
+    /// or, for byte arrays, with zeroes.
+    /// # Examples
+    /// ```text
+    /// var fooArray = new [10];
+    /// var barArray = new [10]b;
+    /// ```
+    NewArray {
+        size: Box<MonkeyCExpression>,
+        is_byte_array: bool,
+        span: Span
+    },
+    /// # Examples
+    /// ```text
+    /// var fooArray = [1, 2, 3];
+    /// var barArray = [0x01, 0x02]b;
+    /// ```
+    Array {
+        elements: Vec<MonkeyCExpression>,
+        is_byte_array: bool,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var fooDict = { :a => 1, "b" => 2 };
+    /// ```
+    Dictionary {
+        entries: Vec<(MonkeyCExpression, MonkeyCExpression)>,
+        span: Span
+    }
+}
+
+impl MonkeyCExpression {
+    /// Location of the whole expression
+    pub fn span(&self) -> Span {
+        match self {
+            MonkeyCExpression::Simple { span, .. } => *span,
+            MonkeyCExpression::Reference { span, .. } => *span,
+            MonkeyCExpression::Mathematical { span, .. } => *span,
+            MonkeyCExpression::Binary { span, .. } => *span,
+            MonkeyCExpression::Bitwise { span, .. } => *span,
+            MonkeyCExpression::Logical { span, .. } => *span,
+            MonkeyCExpression::Unary { span, .. } => *span,
+            MonkeyCExpression::Update { span, .. } => *span,
+            MonkeyCExpression::Ternary { span, .. } => *span,
+            MonkeyCExpression::Elvis { span, .. } => *span,
+            MonkeyCExpression::Assignment { span, .. } => *span,
+            MonkeyCExpression::Cast { span, .. } => *span,
+            MonkeyCExpression::InstanceOf { span, .. } => *span,
+            MonkeyCExpression::Has { span, .. } => *span,
+            MonkeyCExpression::Member { span, .. } => *span,
+            MonkeyCExpression::Index { span, .. } => *span,
+            MonkeyCExpression::Call { span, .. } => *span,
+            MonkeyCExpression::New { span, .. } => *span,
+            MonkeyCExpression::NewArray { span, .. } => *span,
+            MonkeyCExpression::Array { span, .. } => *span,
+            MonkeyCExpression::Dictionary { span, .. } => *span,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+/// Types written after `as`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum MonkeyCTypeExpression {
+    /// # Examples
+    /// ```text
+    /// var fooVar as Lang.Number;
+    /// var barVar as Dictionary<String, Array<Number>>;
+    /// ```
+    Named {
+        /// Qualified name of the type
+        name: String,
+        generics: Vec<MonkeyCTypeExpression>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var fooVar as Number or String or Null;
+    /// ```
+    Union {
+        types: Vec<MonkeyCTypeExpression>,
+        span: Span
+    },
+    /// Shorthand for a union with `Null`.
+    /// # Example
+    /// ```text
+    /// var fooVar as Lang.Object?;
+    /// ```
+    Nullable {
+        inner: Box<MonkeyCTypeExpression>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var callback as Method(x as Number) as Void;
+    /// ```
+    Callback {
+        params: Vec<MonkeyCParameter>,
+        return_type: Option<Box<MonkeyCTypeExpression>>,
+        span: Span
+    },
+    /// A dictionary with known keys.
+    /// # Example
+    /// ```text
+    /// var options as { :a as Number, "b" as String };
+    /// ```
+    DictionaryShape {
+        /// Keys are symbol or string literals
+        entries: Vec<(MonkeyCExpression, MonkeyCTypeExpression)>,
+        span: Span
+    },
+    /// # Example
+    /// ```text
+    /// var pair as [Number, String];
+    /// ```
+    Tuple {
+        types: Vec<MonkeyCTypeExpression>,
+        span: Span
+    }
+}
+
+impl MonkeyCTypeExpression {
+    /// Location of the whole type
+    pub fn span(&self) -> Span {
+        match self {
+            MonkeyCTypeExpression::Named { span, .. } => *span,
+            MonkeyCTypeExpression::Union { span, .. } => *span,
+            MonkeyCTypeExpression::Nullable { span, .. } => *span,
+            MonkeyCTypeExpression::Callback { span, .. } => *span,
+            MonkeyCTypeExpression::DictionaryShape { span, .. } => *span,
+            MonkeyCTypeExpression::Tuple { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MonkeyCLiteralKind {
+    Bool,
+    String,
+    Char,
+    Int,
+    Long,
+    Float,
+    Double,
+    Symbol,
+    Null,
+    Nan
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum MonkeyCExprBitwiseOperation {
     LeftShift,
     RightShift,
@@ -65,7 +678,7 @@ pub enum MonkeyCExprBitwiseOperation {
     Xor
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum MonkeyCExprBinaryOperation {
     LessThan,
     LessThanEquals,
@@ -75,7 +688,7 @@ pub enum MonkeyCExprBinaryOperation {
     NotEquals
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum MonkeyCExprMathOperation {
     Add,
     Subtract,
@@ -83,3 +696,44 @@ pub enum MonkeyCExprMathOperation {
     Multiply,
     Modulo,
 }
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MonkeyCExprLogicalOperation {
+    And,
+    Or
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MonkeyCExprUnaryOperation {
+    /// This one: -
+    Negate,
+    /// This one: !
+    Not,
+    /// This one: ~
+    BitwiseNot
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MonkeyCExprUpdateOperation {
+    Increment,
+    Decrement
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MonkeyCExprAssignmentOperation {
+    /// This one: =
+    Assign,
+    /// This one: +=
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    /// This one: &=
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    /// This one: <<=
+    LeftShift,
+    RightShift
+}
@@ -0,0 +1,276 @@
+use crate::lexer::tokens::TokenKind;
+use crate::parser::ast::{MonkeyCAnnotation, MonkeyCEnumMember, MonkeyCModifier, MonkeyCParameter, MonkeyCStatement, MonkeyCVariableDeclarator};
+use crate::parser::MonkeyCParser;
+use crate::span::Span;
+
+fn modifier(kind: &TokenKind) -> Option<MonkeyCModifier> {
+    match kind {
+        TokenKind::Static => Some(MonkeyCModifier::Static),
+        TokenKind::Hidden => Some(MonkeyCModifier::Hidden),
+        TokenKind::Private => Some(MonkeyCModifier::Private),
+        TokenKind::Protected => Some(MonkeyCModifier::Protected),
+        TokenKind::Public => Some(MonkeyCModifier::Public),
+        _ => None,
+    }
+}
+
+impl MonkeyCParser {
+    /// Returns true if the current token starts a declaration,
+    /// including modifiers and annotations in front of it
+    pub(super) fn at_declaration(&self) -> bool {
+        match self.current_token().kind {
+            TokenKind::Var | TokenKind::Const | TokenKind::Class | TokenKind::Function | TokenKind::Enum |
+            TokenKind::Module | TokenKind::Using | TokenKind::Import => true,
+            TokenKind::OpeningBrace => self.token_at(self.currently_at + 1).kind == TokenKind::SymbolLiteral,
+            ref kind => modifier(kind).is_some(),
+        }
+    }
+
+    /// Parses a declaration together with its annotations and
+    /// modifiers. Returns `None` if the declaration is malformed,
+    /// in which case the error is already recorded.
+    pub(super) fn parse_declaration(&mut self) -> Option<MonkeyCStatement> {
+        self.nested("Declaration", Self::parse_declaration_inner)
+    }
+
+    fn parse_declaration_inner(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        let annotations = self.parse_annotations()?;
+        let mut modifiers = Vec::new();
+        let mut modifier_tokens = Vec::new();
+        while let Some(m) = modifier(&self.current_token().kind) {
+            modifiers.push(m);
+            modifier_tokens.push(self.current_token());
+            self.currently_at += 1;
+        }
+        // These are kept in the tree, but reported
+        let forbidden_on = match self.current_token().kind {
+            TokenKind::Module => Some("modules"),
+            TokenKind::Using => Some("using statements"),
+            TokenKind::Import => Some("imports"),
+            _ => None,
+        };
+        if let Some(declaration) = forbidden_on {
+            for t in &modifier_tokens {
+                self.error_at(t, t.span, &format!("'{}' isn't allowed on {}", t.literal, declaration));
+            }
+        }
+        match self.current_token().kind {
+            TokenKind::Var => self.parse_variable(false, annotations, modifiers, start),
+            TokenKind::Const => self.parse_variable(true, annotations, modifiers, start),
+            TokenKind::Class => self.parse_class(annotations, modifiers, start),
+            TokenKind::Function => self.parse_function(annotations, modifiers, start),
+            TokenKind::Enum => self.parse_enum(annotations, modifiers, start),
+            TokenKind::Module => self.parse_module(annotations, modifiers, start),
+            TokenKind::Using => {
+                self.currently_at += 1;
+                let path = self.parse_qualified_name()?;
+                let mut alias = None;
+                if self.current_token().kind == TokenKind::As {
+                    self.currently_at += 1;
+                    alias = Some(self.expect(TokenKind::Identifier, "an identifier")?.literal);
+                }
+                self.expect(TokenKind::Semicolon, "';'")?;
+                Some(MonkeyCStatement::Using { path, alias, annotations, modifiers, span: self.span_from(start) })
+            }
+            TokenKind::Import => {
+                self.currently_at += 1;
+                let path = self.parse_qualified_name()?;
+                self.expect(TokenKind::Semicolon, "';'")?;
+                Some(MonkeyCStatement::Import { path, annotations, modifiers, span: self.span_from(start) })
+            }
+            _ => {
+                self.error_expected("a declaration");
+                None
+            }
+        }
+    }
+
+    /// Parses annotations like `(:background)`, `(:debug, :test)`
+    /// and `(:typecheck(false))`
+    fn parse_annotations(&mut self) -> Option<Vec<MonkeyCAnnotation>> {
+        let mut annotations = Vec::new();
+        while self.current_token().kind == TokenKind::OpeningBrace {
+            self.currently_at += 1;
+            loop {
+                let name = self.expect(TokenKind::SymbolLiteral, "an annotation")?;
+                let args = match self.current_token().kind {
+                    TokenKind::OpeningBrace => self.parse_arguments()?,
+                    _ => Vec::new(),
+                };
+                annotations.push(MonkeyCAnnotation { name: name.literal, args, span: self.span_from(name.span) });
+                if self.current_token().kind != TokenKind::Comma {
+                    break;
+                }
+                self.currently_at += 1;
+            }
+            self.expect(TokenKind::ClosingBrace, "')'")?;
+        }
+        Some(annotations)
+    }
+
+    fn parse_variable(&mut self, is_const: bool, annotations: Vec<MonkeyCAnnotation>, modifiers: Vec<MonkeyCModifier>, start: Span) -> Option<MonkeyCStatement> {
+        self.currently_at += 1;
+        let mut declarators = Vec::new();
+        loop {
+            let name = self.expect(TokenKind::Identifier, "an identifier")?;
+            let mut var_type = None;
+            if self.current_token().kind == TokenKind::As {
+                self.currently_at += 1;
+                var_type = Some(self.parse_type()?);
+            }
+            let mut default_val = None;
+            if self.current_token().kind == TokenKind::Assign {
+                self.currently_at += 1;
+                default_val = Some(self.parse_expression()?);
+            } else if is_const {
+                self.error_expected("'='");
+                return None;
+            }
+            declarators.push(MonkeyCVariableDeclarator { name: name.literal, var_type, default_val, span: self.span_from(name.span) });
+
+            if self.current_token().kind != TokenKind::Comma {
+                break;
+            }
+            self.currently_at += 1;
+        }
+        self.expect(TokenKind::Semicolon, "';'")?;
+        Some(MonkeyCStatement::VariableDeclaration {
+            declarators,
+            is_const,
+            annotations,
+            modifiers,
+            span: self.span_from(start)
+        })
+    }
+
+    fn parse_class(&mut self, annotations: Vec<MonkeyCAnnotation>, modifiers: Vec<MonkeyCModifier>, start: Span) -> Option<MonkeyCStatement> {
+        self.currently_at += 1;
+        let name = self.expect(TokenKind::Identifier, "a class name")?.literal;
+        let mut extends = None;
+        if self.current_token().kind == TokenKind::Extends {
+            self.currently_at += 1;
+            extends = Some(self.parse_qualified_name()?);
+        }
+        let children = self.parse_members("a class member")?;
+        Some(MonkeyCStatement::ClassDeclaration { name, extends, annotations, modifiers, children, span: self.span_from(start) })
+    }
+
+    fn parse_module(&mut self, annotations: Vec<MonkeyCAnnotation>, modifiers: Vec<MonkeyCModifier>, start: Span) -> Option<MonkeyCStatement> {
+        self.currently_at += 1;
+        let name = self.expect(TokenKind::Identifier, "a module name")?.literal;
+        let children = self.parse_members("a module member")?;
+        Some(MonkeyCStatement::ModuleDeclaration { name, annotations, modifiers, children, span: self.span_from(start) })
+    }
+
+    /// Parses declarations between `{` and `}` of a class or a
+    /// module. `member` describes what's expected in error messages.
+    fn parse_members(&mut self, member: &str) -> Option<Vec<MonkeyCStatement>> {
+        self.expect(TokenKind::OpeningBracket, "'{'")?;
+        let mut children = Vec::new();
+        loop {
+            match self.current_token().kind {
+                TokenKind::ClosingBracket => break,
+                TokenKind::Semicolon => self.currently_at += 1,
+                TokenKind::Eof => {
+                    // Everything up to here is still worth keeping
+                    self.error_expected("'}'");
+                    return Some(children);
+                }
+                _ if self.at_declaration() => children.extend(self.recovering(Self::parse_declaration)),
+                _ => children.extend(self.recovering(|parser| {
+                    parser.error_expected(member);
+                    None
+                })),
+            }
+        }
+        self.currently_at += 1;
+        Some(children)
+    }
+
+    /// Parses `enum Name { A = 1, B }`. The name is optional.
+    fn parse_enum(&mut self, annotations: Vec<MonkeyCAnnotation>, modifiers: Vec<MonkeyCModifier>, start: Span) -> Option<MonkeyCStatement> {
+        self.currently_at += 1;
+        let mut name = None;
+        if self.current_token().kind == TokenKind::Identifier {
+            name = Some(self.current_token().literal);
+            self.currently_at += 1;
+        }
+        self.expect(TokenKind::OpeningBracket, "'{'")?;
+
+        let mut members = Vec::new();
+        while self.current_token().kind != TokenKind::ClosingBracket {
+            let member = self.expect(TokenKind::Identifier, "an enum member")?;
+            let mut value = None;
+            if self.current_token().kind == TokenKind::Assign {
+                self.currently_at += 1;
+                value = Some(self.parse_expression()?);
+            }
+            members.push(MonkeyCEnumMember { name: member.literal, value, span: self.span_from(member.span) });
+            if self.current_token().kind == TokenKind::Comma {
+                self.currently_at += 1;
+            } else if self.current_token().kind != TokenKind::ClosingBracket {
+                self.error_expected("',' or '}'");
+                return None;
+            }
+        }
+        self.currently_at += 1;
+        Some(MonkeyCStatement::EnumDeclaration { name, members, annotations, modifiers, span: self.span_from(start) })
+    }
+
+    fn parse_function(&mut self, annotations: Vec<MonkeyCAnnotation>, modifiers: Vec<MonkeyCModifier>, start: Span) -> Option<MonkeyCStatement> {
+        self.currently_at += 1;
+        let name = self.expect(TokenKind::Identifier, "a function name")?.literal;
+
+        let params = self.parse_parameters()?;
+        let mut return_type = None;
+        if self.current_token().kind == TokenKind::As {
+            self.currently_at += 1;
+            return_type = Some(self.parse_type()?);
+        }
+        let body = self.parse_block()?;
+        Some(MonkeyCStatement::FunctionDeclaration {
+            name,
+            params,
+            return_type,
+            annotations,
+            modifiers,
+            body: Box::new(body),
+            span: self.span_from(start)
+        })
+    }
+
+    /// Parses `(a as Number, b)`, starting at the opening brace
+    pub(super) fn parse_parameters(&mut self) -> Option<Vec<MonkeyCParameter>> {
+        self.expect(TokenKind::OpeningBrace, "'('")?;
+        let mut params = Vec::new();
+        while self.current_token().kind != TokenKind::ClosingBrace {
+            let param = self.expect(TokenKind::Identifier, "a parameter name")?;
+            let mut param_type = None;
+            if self.current_token().kind == TokenKind::As {
+                self.currently_at += 1;
+                param_type = Some(self.parse_type()?);
+            }
+            params.push(MonkeyCParameter { name: param.literal, param_type, span: self.span_from(param.span) });
+            if self.current_token().kind == TokenKind::Comma {
+                self.currently_at += 1;
+            } else if self.current_token().kind != TokenKind::ClosingBrace {
+                self.error_expected("',' or ')'");
+                return None;
+            }
+        }
+        self.currently_at += 1;
+        Some(params)
+    }
+
+    /// Parses a name like `Toybox.Application.AppBase`
+    pub(super) fn parse_qualified_name(&mut self) -> Option<String> {
+        let mut name = self.expect(TokenKind::Identifier, "an identifier")?.literal;
+        while self.current_token().kind == TokenKind::Dot {
+            self.currently_at += 1;
+            name.push('.');
+            name.push_str(&self.expect(TokenKind::Identifier, "an identifier")?.literal);
+        }
+        Some(name)
+    }
+}
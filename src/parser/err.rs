@@ -1,7 +1,11 @@
-#[derive(Debug)]
+use crate::span::Span;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
 pub struct MCParseError {
     pub at: (u64, u64),
-    pub literal_len: usize,
+    /// Exact range of the erroneous part
+    pub span: Span,
     pub full_msg: String,
     pub msg: String,
 }
\ No newline at end of file
@@ -0,0 +1,391 @@
+use crate::lexer::tokens::TokenKind;
+use crate::parser::ast::{MonkeyCExpression, MonkeyCExprAssignmentOperation, MonkeyCExprBinaryOperation,
+                         MonkeyCExprBitwiseOperation, MonkeyCExprLogicalOperation, MonkeyCExprMathOperation, MonkeyCExprUnaryOperation,
+                         MonkeyCExprUpdateOperation, MonkeyCLiteralKind};
+use crate::parser::MonkeyCParser;
+use crate::span::Span;
+
+/// How tightly a binary operator binds, following the
+/// operator table of the Monkey C reference. Operators
+/// with a higher number bind tighter, and all of them
+/// are left-associative. Casts with `as` bind tightest.
+fn binary_precedence(kind: &TokenKind) -> Option<u8> {
+    match kind {
+        TokenKind::As => Some(6),
+        TokenKind::DoubleVerticalBar | TokenKind::Or => Some(1),
+        TokenKind::DoubleAmpersand | TokenKind::And => Some(2),
+        TokenKind::Equals | TokenKind::NotEquals | TokenKind::LessThan | TokenKind::LessThanEquals |
+        TokenKind::GreaterThan | TokenKind::GreaterThanEquals | TokenKind::InstanceOf | TokenKind::Has => Some(3),
+        TokenKind::Plus | TokenKind::Minus | TokenKind::VerticalBar | TokenKind::Caret => Some(4),
+        TokenKind::Asterisk | TokenKind::Slash | TokenKind::Percent | TokenKind::Ampersand |
+        TokenKind::LeftShift | TokenKind::RightShift => Some(5),
+        _ => None,
+    }
+}
+
+/// Puts `lhs` and `rhs` together into a node for the binary operator `op`
+fn binary_expression(op: &TokenKind, lhs: MonkeyCExpression, rhs: MonkeyCExpression, span: Span) -> MonkeyCExpression {
+    let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+    if let Some(op) = math_operation(op) {
+        MonkeyCExpression::Mathematical { lhs, op, rhs, span }
+    } else if let Some(op) = comparison_operation(op) {
+        MonkeyCExpression::Binary { lhs, op, rhs, span }
+    } else if let Some(op) = bitwise_operation(op) {
+        MonkeyCExpression::Bitwise { lhs, op, rhs, span }
+    } else if let Some(op) = logical_operation(op) {
+        MonkeyCExpression::Logical { lhs, op, rhs, span }
+    } else if *op == TokenKind::InstanceOf {
+        MonkeyCExpression::InstanceOf { value: lhs, class: rhs, span }
+    } else {
+        MonkeyCExpression::Has { value: lhs, member: rhs, span }
+    }
+}
+
+fn math_operation(kind: &TokenKind) -> Option<MonkeyCExprMathOperation> {
+    match kind {
+        TokenKind::Plus => Some(MonkeyCExprMathOperation::Add),
+        TokenKind::Minus => Some(MonkeyCExprMathOperation::Subtract),
+        TokenKind::Asterisk => Some(MonkeyCExprMathOperation::Multiply),
+        TokenKind::Slash => Some(MonkeyCExprMathOperation::Divide),
+        TokenKind::Percent => Some(MonkeyCExprMathOperation::Modulo),
+        _ => None,
+    }
+}
+
+fn comparison_operation(kind: &TokenKind) -> Option<MonkeyCExprBinaryOperation> {
+    match kind {
+        TokenKind::Equals => Some(MonkeyCExprBinaryOperation::Equals),
+        TokenKind::NotEquals => Some(MonkeyCExprBinaryOperation::NotEquals),
+        TokenKind::LessThan => Some(MonkeyCExprBinaryOperation::LessThan),
+        TokenKind::LessThanEquals => Some(MonkeyCExprBinaryOperation::LessThanEquals),
+        TokenKind::GreaterThan => Some(MonkeyCExprBinaryOperation::GreaterThan),
+        TokenKind::GreaterThanEquals => Some(MonkeyCExprBinaryOperation::GreaterThanEquals),
+        _ => None,
+    }
+}
+
+fn bitwise_operation(kind: &TokenKind) -> Option<MonkeyCExprBitwiseOperation> {
+    match kind {
+        TokenKind::Ampersand => Some(MonkeyCExprBitwiseOperation::And),
+        TokenKind::VerticalBar => Some(MonkeyCExprBitwiseOperation::Or),
+        TokenKind::Caret => Some(MonkeyCExprBitwiseOperation::Xor),
+        TokenKind::LeftShift => Some(MonkeyCExprBitwiseOperation::LeftShift),
+        TokenKind::RightShift => Some(MonkeyCExprBitwiseOperation::RightShift),
+        _ => None,
+    }
+}
+
+fn logical_operation(kind: &TokenKind) -> Option<MonkeyCExprLogicalOperation> {
+    match kind {
+        TokenKind::DoubleAmpersand | TokenKind::And => Some(MonkeyCExprLogicalOperation::And),
+        TokenKind::DoubleVerticalBar | TokenKind::Or => Some(MonkeyCExprLogicalOperation::Or),
+        _ => None,
+    }
+}
+
+fn assignment_operation(kind: &TokenKind) -> Option<MonkeyCExprAssignmentOperation> {
+    match kind {
+        TokenKind::Assign => Some(MonkeyCExprAssignmentOperation::Assign),
+        TokenKind::PlusAssign => Some(MonkeyCExprAssignmentOperation::Add),
+        TokenKind::MinusAssign => Some(MonkeyCExprAssignmentOperation::Subtract),
+        TokenKind::AsteriskAssign => Some(MonkeyCExprAssignmentOperation::Multiply),
+        TokenKind::SlashAssign => Some(MonkeyCExprAssignmentOperation::Divide),
+        TokenKind::PercentAssign => Some(MonkeyCExprAssignmentOperation::Modulo),
+        TokenKind::AmpersandAssign => Some(MonkeyCExprAssignmentOperation::BitwiseAnd),
+        TokenKind::VerticalBarAssign => Some(MonkeyCExprAssignmentOperation::BitwiseOr),
+        TokenKind::CaretAssign => Some(MonkeyCExprAssignmentOperation::BitwiseXor),
+        TokenKind::LeftShiftAssign => Some(MonkeyCExprAssignmentOperation::LeftShift),
+        TokenKind::RightShiftAssign => Some(MonkeyCExprAssignmentOperation::RightShift),
+        _ => None,
+    }
+}
+
+fn literal_kind(kind: &TokenKind) -> Option<MonkeyCLiteralKind> {
+    match kind {
+        TokenKind::BoolLiteral => Some(MonkeyCLiteralKind::Bool),
+        TokenKind::StringLiteral => Some(MonkeyCLiteralKind::String),
+        TokenKind::CharLiteral => Some(MonkeyCLiteralKind::Char),
+        TokenKind::IntLiteral => Some(MonkeyCLiteralKind::Int),
+        TokenKind::LongLiteral => Some(MonkeyCLiteralKind::Long),
+        TokenKind::FloatLiteral => Some(MonkeyCLiteralKind::Float),
+        TokenKind::DoubleLiteral => Some(MonkeyCLiteralKind::Double),
+        TokenKind::SymbolLiteral => Some(MonkeyCLiteralKind::Symbol),
+        TokenKind::Null => Some(MonkeyCLiteralKind::Null),
+        TokenKind::Nan => Some(MonkeyCLiteralKind::Nan),
+        _ => None,
+    }
+}
+
+/// Returns true for tokens that an expression can start with
+pub(super) fn starts_expression(kind: &TokenKind) -> bool {
+    literal_kind(kind).is_some() ||
+        matches!(kind, TokenKind::Identifier | TokenKind::Self_ | TokenKind::Me | TokenKind::Dollar |
+                 TokenKind::OpeningBrace | TokenKind::OpeningSquareBracket | TokenKind::OpeningBracket |
+                 TokenKind::New | TokenKind::Minus | TokenKind::Bang | TokenKind::Tilde |
+                 TokenKind::Increment | TokenKind::Decrement)
+}
+
+impl MonkeyCParser {
+    /// Parses an expression that may also be an assignment.
+    /// Assignments are only allowed where a statement is.
+    pub(super) fn parse_assignment(&mut self) -> Option<MonkeyCExpression> {
+        let start = self.current_token();
+        let target = self.parse_expression()?;
+        let op = match assignment_operation(&self.current_token().kind) {
+            Some(op) => op,
+            None => return Some(target),
+        };
+        if !matches!(target, MonkeyCExpression::Reference { .. } | MonkeyCExpression::Member { .. } | MonkeyCExpression::Index { .. }) {
+            self.error_at(&start, target.span(), "Invalid assignment target");
+            return None;
+        }
+        self.currently_at += 1;
+        let value = self.parse_expression()?;
+        Some(MonkeyCExpression::Assignment { target: Box::new(target), op, value: Box::new(value), span: self.span_from(start.span) })
+    }
+
+    /// Parses a whole expression, starting at the current
+    /// token. Returns `None` if the expression is malformed,
+    /// in which case the error is already recorded.
+    pub(super) fn parse_expression(&mut self) -> Option<MonkeyCExpression> {
+        self.nested("Expression", Self::parse_ternary)
+    }
+
+    fn parse_ternary(&mut self) -> Option<MonkeyCExpression> {
+        let start = self.current_token().span;
+        let condition = self.parse_binary(1)?;
+        match self.current_token().kind {
+            TokenKind::QuestionMark => {
+                self.currently_at += 1;
+                let if_true = self.parse_expression()?;
+                self.expect(TokenKind::Colon, "':'")?;
+                let if_false = self.parse_expression()?;
+                Some(MonkeyCExpression::Ternary {
+                    condition: Box::new(condition),
+                    if_true: Box::new(if_true),
+                    if_false: Box::new(if_false),
+                    span: self.span_from(start)
+                })
+            }
+            TokenKind::Elvis => {
+                self.currently_at += 1;
+                let rhs = self.parse_expression()?;
+                Some(MonkeyCExpression::Elvis { lhs: Box::new(condition), rhs: Box::new(rhs), span: self.span_from(start) })
+            }
+            _ => Some(condition),
+        }
+    }
+
+    /// Parses binary operators that bind at least as
+    /// tightly as `min_precedence`
+    fn parse_binary(&mut self, min_precedence: u8) -> Option<MonkeyCExpression> {
+        let start = self.current_token().span;
+        let depth = self.depth;
+        let mut lhs = self.parse_unary()?;
+        loop {
+            let op = self.current_token().kind;
+            let precedence = match binary_precedence(&op) {
+                Some(p) if p >= min_precedence => p,
+                _ => break,
+            };
+            self.deeper("Expression")?;
+            self.currently_at += 1;
+            if op == TokenKind::As {
+                let target_type = self.parse_cast_type()?;
+                lhs = MonkeyCExpression::Cast { value: Box::new(lhs), target_type, span: self.span_from(start) };
+                continue;
+            }
+            let rhs = self.parse_binary(precedence + 1)?;
+            lhs = binary_expression(&op, lhs, rhs, self.span_from(start));
+        }
+        self.depth = depth;
+        Some(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Option<MonkeyCExpression> {
+        let start = self.current_token().span;
+        let op = match self.current_token().kind {
+            TokenKind::Minus => MonkeyCExprUnaryOperation::Negate,
+            TokenKind::Bang => MonkeyCExprUnaryOperation::Not,
+            TokenKind::Tilde => MonkeyCExprUnaryOperation::BitwiseNot,
+            TokenKind::Increment | TokenKind::Decrement => {
+                let op = if self.current_token().kind == TokenKind::Increment {
+                    MonkeyCExprUpdateOperation::Increment
+                } else {
+                    MonkeyCExprUpdateOperation::Decrement
+                };
+                self.currently_at += 1;
+                let operand = self.nested("Expression", Self::parse_unary)?;
+                return Some(MonkeyCExpression::Update { op, operand: Box::new(operand), is_prefix: true, span: self.span_from(start) });
+            }
+            _ => return self.parse_postfix(),
+        };
+        self.currently_at += 1;
+        let operand = self.nested("Expression", Self::parse_unary)?;
+        Some(MonkeyCExpression::Unary { op, operand: Box::new(operand), span: self.span_from(start) })
+    }
+
+    /// Parses member accesses, indexing, calls and
+    /// increments that follow a primary expression
+    fn parse_postfix(&mut self) -> Option<MonkeyCExpression> {
+        let start = self.current_token().span;
+        let depth = self.depth;
+        let mut expression = self.parse_primary()?;
+        loop {
+            expression = match self.current_token().kind {
+                TokenKind::Dot => {
+                    self.deeper("Expression")?;
+                    self.currently_at += 1;
+                    let name = self.expect(TokenKind::Identifier, "an identifier")?.literal;
+                    MonkeyCExpression::Member { object: Box::new(expression), name, span: self.span_from(start) }
+                }
+                TokenKind::OpeningSquareBracket => {
+                    self.deeper("Expression")?;
+                    self.currently_at += 1;
+                    let index = self.parse_expression()?;
+                    self.expect(TokenKind::ClosingSquareBracket, "']'")?;
+                    MonkeyCExpression::Index { object: Box::new(expression), index: Box::new(index), span: self.span_from(start) }
+                }
+                TokenKind::OpeningBrace => {
+                    self.deeper("Expression")?;
+                    let args = self.parse_arguments()?;
+                    MonkeyCExpression::Call { callee: Box::new(expression), args, span: self.span_from(start) }
+                }
+                TokenKind::Increment | TokenKind::Decrement => {
+                    self.deeper("Expression")?;
+                    let op = if self.current_token().kind == TokenKind::Increment {
+                        MonkeyCExprUpdateOperation::Increment
+                    } else {
+                        MonkeyCExprUpdateOperation::Decrement
+                    };
+                    self.currently_at += 1;
+                    MonkeyCExpression::Update { op, operand: Box::new(expression), is_prefix: false, span: self.span_from(start) }
+                }
+                _ => {
+                    self.depth = depth;
+                    return Some(expression);
+                }
+            };
+        }
+    }
+
+    fn parse_primary(&mut self) -> Option<MonkeyCExpression> {
+        let t = self.current_token();
+        if let Some(kind) = literal_kind(&t.kind) {
+            self.currently_at += 1;
+            return Some(MonkeyCExpression::Simple { value: t.literal, kind, span: t.span });
+        }
+        match t.kind {
+            TokenKind::Identifier | TokenKind::Self_ | TokenKind::Me | TokenKind::Dollar => {
+                self.currently_at += 1;
+                Some(MonkeyCExpression::Reference { name: t.literal, span: t.span })
+            }
+            // The lexer takes `:name` for the end of a ternary when a `?` is still open,
+            // which can be the `?` of a nullable type. Here it can only be a symbol.
+            TokenKind::Colon if self.token_at(self.currently_at + 1).kind == TokenKind::Identifier &&
+                self.token_at(self.currently_at + 1).span.start == t.span.end => {
+                let name = self.token_at(self.currently_at + 1);
+                self.currently_at += 2;
+                Some(MonkeyCExpression::Simple { value: name.literal, kind: MonkeyCLiteralKind::Symbol, span: t.span.to(name.span) })
+            }
+            TokenKind::OpeningBrace => {
+                self.currently_at += 1;
+                let expression = self.parse_expression()?;
+                self.expect(TokenKind::ClosingBrace, "')'")?;
+                Some(expression)
+            }
+            TokenKind::OpeningSquareBracket => self.parse_array(),
+            TokenKind::OpeningBracket => self.parse_dictionary(),
+            TokenKind::New => self.parse_new(),
+            _ => {
+                self.error_expected("an expression");
+                None
+            }
+        }
+    }
+
+    /// Parses `[1, 2, 3]` and `[1, 2, 3]b`
+    fn parse_array(&mut self) -> Option<MonkeyCExpression> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let elements = self.parse_list(TokenKind::ClosingSquareBracket, "']'", Self::parse_expression)?;
+        let is_byte_array = self.eat_byte_array_suffix();
+        Some(MonkeyCExpression::Array { elements, is_byte_array, span: self.span_from(start) })
+    }
+
+    /// Parses `{ :a => 1, "b" => 2 }`
+    fn parse_dictionary(&mut self) -> Option<MonkeyCExpression> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let entries = self.parse_list(TokenKind::ClosingBracket, "'}'", |parser| {
+            let key = parser.parse_expression()?;
+            parser.expect(TokenKind::Arrow, "'=>'")?;
+            let value = parser.parse_expression()?;
+            Some((key, value))
+        })?;
+        Some(MonkeyCExpression::Dictionary { entries, span: self.span_from(start) })
+    }
+
+    /// Parses `new Class(args)` and `new [size]`
+    fn parse_new(&mut self) -> Option<MonkeyCExpression> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        if self.current_token().kind == TokenKind::OpeningSquareBracket {
+            self.currently_at += 1;
+            let size = self.parse_expression()?;
+            self.expect(TokenKind::ClosingSquareBracket, "']'")?;
+            let is_byte_array = self.eat_byte_array_suffix();
+            return Some(MonkeyCExpression::NewArray { size: Box::new(size), is_byte_array, span: self.span_from(start) });
+        }
+
+        let name = self.expect(TokenKind::Identifier, "a class name")?;
+        let mut class = MonkeyCExpression::Reference { name: name.literal, span: name.span };
+        while self.current_token().kind == TokenKind::Dot {
+            self.currently_at += 1;
+            let member = self.expect(TokenKind::Identifier, "an identifier")?.literal;
+            class = MonkeyCExpression::Member { object: Box::new(class), name: member, span: self.span_from(name.span) };
+        }
+        if self.current_token().kind != TokenKind::OpeningBrace {
+            self.error_expected("'('");
+            return None;
+        }
+        let args = self.parse_arguments()?;
+        Some(MonkeyCExpression::New { class: Box::new(class), args, span: self.span_from(start) })
+    }
+
+    /// Parses `(a, b, c)`, starting at the opening brace
+    pub(super) fn parse_arguments(&mut self) -> Option<Vec<MonkeyCExpression>> {
+        self.expect(TokenKind::OpeningBrace, "'('")?;
+        self.parse_list(TokenKind::ClosingBrace, "')'", Self::parse_expression)
+    }
+
+    /// Parses comma-separated items up to and including
+    /// the `end` token. A trailing comma is allowed.
+    fn parse_list<T>(&mut self, end: TokenKind, end_name: &str,
+                     mut parse_item: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        let mut items = Vec::new();
+        while self.current_token().kind != end {
+            items.push(parse_item(self)?);
+            if self.current_token().kind == TokenKind::Comma {
+                self.currently_at += 1;
+            } else if self.current_token().kind != end {
+                self.error_expected(&format!("',' or {}", end_name));
+                return None;
+            }
+        }
+        self.currently_at += 1;
+        Some(items)
+    }
+
+    /// Skips the `b` right after the closing square
+    /// bracket of a byte array, if it's there
+    fn eat_byte_array_suffix(&mut self) -> bool {
+        let previous = self.token_at(self.currently_at - 1);
+        let t = self.current_token();
+        if t.kind == TokenKind::Identifier && t.literal == "b" && t.span.start == previous.span.end {
+            self.currently_at += 1;
+            return true;
+        }
+        false
+    }
+}
@@ -1,15 +1,21 @@
 use crate::lexer::tokens::{Token, TokenKind};
-use crate::parser::ast::{MonkeyCStatement, MonkeyCExpression};
+use crate::parser::ast::MonkeyCStatement;
 use crate::parser::err::MCParseError;
-use std::process;
+use crate::span::Span;
 use std::path::PathBuf;
 
-pub(crate) mod ast;
-mod err;
+pub mod ast;
+mod decl;
+pub mod err;
+mod expr;
+mod stmt;
+pub mod printer;
+mod types;
+pub mod visit;
 
 macro_rules! syntax_expect_fmt {
     ($file:expr, $expected:expr, $actual:expr) => {
-        format!("Syntax error at {}:{}:{}: Expected {}, found '{}'", $file, $actual.row, $actual.column, $expected, $actual.literal);
+        format!("Syntax error at {}:{}:{}: Expected {}, found {}", $file, $actual.row, $actual.column, $expected, $actual.describe())
     }
 }
 
@@ -18,352 +24,205 @@ macro_rules! syntax_expect_fmt {
 /// start
 macro_rules! syntax_expect_fmt_headl {
     ($expected:expr, $actual:expr) => {
-        format!("Expected {}, found '{}'", $expected, $actual.literal);
+        format!("Expected {}, found {}", $expected, $actual.describe())
     }
 }
 
+/// How deep expressions, types and statements can be nested.
+/// Deeper code is reported instead of being parsed, so that
+/// the parser doesn't run out of stack. Every level takes up
+/// to about 15 KiB of stack in a debug build, and this keeps
+/// the parser within the 2 MiB that spawned threads get.
+const MAX_NESTING: usize = 100;
+
 pub struct MonkeyCParser {
     token_list: Vec<Token>,
     file_path: PathBuf,
     currently_at: usize,
+    errors: Vec<MCParseError>,
+    /// Number of `nested` calls that are in progress
+    depth: usize,
 }
 
 impl MonkeyCParser {
-    pub fn new(token_list: Vec<Token>, file_path: PathBuf) -> Self {
+    pub fn new(mut token_list: Vec<Token>, file_path: PathBuf) -> Self {
+        // The parser relies on the list ending with an end of file token
+        if token_list.last().map(|t| &t.kind) != Some(&TokenKind::Eof) {
+            let (row, column, span) = token_list.last().map_or((1, 1, Default::default()), |t| (t.row, t.column, t.span));
+            let mut eof = Token::new(TokenKind::Eof, String::new(), row, column);
+            eof.span = span;
+            token_list.push(eof);
+        }
         Self {
             token_list,
             file_path,
-            currently_at: 0
+            currently_at: 0,
+            errors: Vec::new(),
+            depth: 0
         }
     }
 
-    /// Returns true if `k` in arguments is one of
-    /// `BoolLiteral`, `StringLiteral`, `IntLiteral`,
-    /// `LongLiteral`, `FloatLiteral`, `DoubleLiteral`,
-    /// `Null`, or `CharLiteral`.
-    fn is_kind_a_type(&self, k: TokenKind) -> bool {
-        if k == TokenKind::BoolLiteral || k == TokenKind::CharLiteral || k == TokenKind::StringLiteral || k == TokenKind::CharLiteral || k == TokenKind::LongLiteral || k == TokenKind::DoubleLiteral || k == TokenKind::FloatLiteral || k == TokenKind::IntLiteral {
-            true
-        } else {
-            false
+    /// Returns a token at `index`, or the end of file
+    /// token if `index` is past the end of the list
+    fn token_at(&self, index: usize) -> Token {
+        match self.token_list.get(index) {
+            Some(t) => t.clone(),
+            None => self.token_list[self.token_list.len() - 1].clone(),
         }
     }
 
     fn current_token(&self) -> Token {
-        self.token_list.get(self.currently_at).unwrap().clone()
+        self.token_at(self.currently_at)
     }
 
-    //noinspection DuplicatedCode
-    pub fn parse(&mut self) -> Result<Vec<MonkeyCStatement>, Vec<MCParseError>> {
-        let mut statements: Vec<MonkeyCStatement> = Vec::new();
-        let mut errors: Vec<MCParseError> = Vec::new();
+    /// Span from the start of `start` to the end
+    /// of the last consumed token
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.token_at(self.currently_at.saturating_sub(1)).span)
+    }
 
-        // For context saving, for example when we
-        // are inside a class statement, we write
-        // received data here
-        let context: Option<MonkeyCStatement> = None;
+    /// Records an "Expected `expected`, found ..."
+    /// error at the current token
+    fn error_expected(&mut self, expected: &str) {
+        let t = self.current_token();
+        self.errors.push(MCParseError {
+            at: (t.row, t.column),
+            span: t.span,
+            full_msg: syntax_expect_fmt!(self.file_path.display(), expected, t),
+            msg: syntax_expect_fmt_headl!(expected, t)
+        });
+    }
 
-        // This is for understanding whether we finished to parse a line
-        let mut line_finished = true;
-        while self.token_list.len() > self.currently_at {
-            let t = self.current_token();
+    /// Records an error with a custom message that
+    /// starts at `token` and covers `span`
+    fn error_at(&mut self, token: &Token, span: Span, msg: &str) {
+        self.errors.push(MCParseError {
+            at: (token.row, token.column),
+            span,
+            full_msg: format!("Syntax error at {}:{}:{}: {}", self.file_path.display(), token.row, token.column, msg),
+            msg: msg.to_string()
+        });
+    }
 
-            match t.kind {
-                TokenKind::And => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Break => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Case => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Catch => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Class => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Const => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Continue => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Default => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Do => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Else => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Enum => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Extends => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Finally => {
-                    self.currently_at += 1;
-                }
-                TokenKind::For => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Function => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Has => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Hidden => {
-                    self.currently_at += 1;
-                }
-                TokenKind::If => {
-                    self.currently_at += 1;
-                }
-                TokenKind::InstanceOf => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Import => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Me => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Module => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Private => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Protected => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Public => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Or => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Return => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Self_ => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Static => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Switch => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Throw => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Try => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Using => {
-                    self.currently_at += 1;
-                },
-                TokenKind::Var => {
-                    let mut name: String = String::new();
-                    let mut var_type: Option<String> = None;
-                    let mut default_val: MonkeyCExpression;
+    /// Consumes the current token if it's of `kind`.
+    /// Otherwise records an error and leaves it alone.
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Option<Token> {
+        let t = self.current_token();
+        if t.kind != kind {
+            self.error_expected(expected);
+            return None;
+        }
+        self.currently_at += 1;
+        Some(t)
+    }
 
-                    if !line_finished {
-                        let t = self.token_list.get(self.currently_at - 1).unwrap().clone();
-                        errors.push(MCParseError {
-                            at: (t.row, t.column + 1),
-                            literal_len: t.literal.len(),
-                            full_msg: syntax_expect_fmt!(self.file_path.clone().into_os_string().to_str().unwrap(), "at least ';' token", t),
-                            msg: syntax_expect_fmt_headl!("at least ';' token", t)
-                        });
-                    }
+    /// Runs `parse` one nesting level deeper. Past `MAX_NESTING`
+    /// levels, records that `what` is nested too deeply instead.
+    fn nested<T>(&mut self, what: &str, parse: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        let depth = self.depth;
+        self.deeper(what)?;
+        let result = parse(self);
+        self.depth = depth;
+        result
+    }
 
-                    self.currently_at += 1;
+    /// Adds a nesting level for a node that a loop wraps around
+    /// the node before it, like the second `+` in `a + b + c`.
+    /// The loop resets `depth` when it's done, and if it fails
+    /// halfway, the `nested` call around it does that.
+    fn deeper(&mut self, what: &str) -> Option<()> {
+        if self.depth >= MAX_NESTING {
+            let t = self.current_token();
+            self.error_at(&t, t.span, &format!("{} is nested too deeply", what));
+            return None;
+        }
+        self.depth += 1;
+        Some(())
+    }
 
-                    line_finished = false;
-                    if self.token_list.get(self.currently_at) != None {
-                        if self.current_token().kind != TokenKind::Identifier {
-                            let t = self.current_token();
-                            errors.push(MCParseError {
-                                at: (t.row, t.column),
-                                literal_len: t.literal.len(),
-                                full_msg: syntax_expect_fmt!(self.file_path.clone().into_os_string().to_str().unwrap(), "an identifier", t),
-                                msg: syntax_expect_fmt_headl!("an identifier", t)
-                            });
-                        }
-                        name = self.current_token().literal;
-                        self.currently_at += 1;
-                        match self.current_token().kind {
-                            TokenKind::Assign => {
-                                self.currently_at += 1;
-                                if !self.is_kind_a_type(self.current_token().kind) && self.current_token().kind != TokenKind::Identifier {
-                                    let t = self.current_token();
-                                    errors.push(MCParseError {
-                                        at: (t.row, t.column),
-                                        literal_len: t.literal.len(),
-                                        full_msg: syntax_expect_fmt!(self.file_path.clone().into_os_string().to_str().unwrap(), "an identifier or literal", t),
-                                        msg: syntax_expect_fmt_headl!("an identifier or literal", t)
-                                    });
-                                }
-                            }
-                            TokenKind::As => {
-                                self.currently_at += 1;
-                                if self.current_token().kind != TokenKind::Identifier {
-                                    let t = self.current_token();
-                                    errors.push(MCParseError {
-                                        at: (t.row, t.column),
-                                        literal_len: t.literal.len(),
-                                        full_msg: syntax_expect_fmt!(self.file_path.clone().into_os_string().to_str().unwrap(), "an identifier", t),
-                                        msg: syntax_expect_fmt_headl!("an identifier", t)
-                                    });
-                                }
-                                var_type = Some(self.current_token().literal);
-                                self.currently_at += 2;
-                                if self.current_token().kind != TokenKind::Identifier && !self.is_kind_a_type(self.current_token().kind) {
-                                    let t = self.current_token();
-                                    errors.push(MCParseError {
-                                        at: (t.row, t.column),
-                                        literal_len: t.literal.len(),
-                                        full_msg: syntax_expect_fmt!(self.file_path.clone().into_os_string().to_str().unwrap(), "an identifier or literal", t),
-                                        msg: syntax_expect_fmt_headl!("an identifier or literal", t)
-                                    });
-                                }
-                            }
-                            _ => {
-                                let t = self.current_token();
-                                errors.push(MCParseError {
-                                    at: (t.row, t.column),
-                                    literal_len: t.literal.len(),
-                                    full_msg: syntax_expect_fmt!(self.file_path.clone().into_os_string().to_str().unwrap(), "an '=' or 'as' token", t),
-                                    msg: syntax_expect_fmt_headl!("an '=' or 'as' token", t)
-                                });
-                            }
-                        }
-                        default_val = if !self.is_kind_a_type(self.current_token().kind) {
-                            MonkeyCExpression::Reference(self.current_token().literal)
-                        } else {
-                            MonkeyCExpression::Simple(self.current_token().literal)
-                        };
-                    } else {
-                        eprintln!("Unexpected end of file");
-                        process::exit(1);
-                    }
-                    let statement = MonkeyCStatement::VariableDeclaration {
-                        name,
-                        default_val,
-                        var_type,
-                        is_const: false
-                    };
-                    statements.push(statement);
-                    self.currently_at += 1;
-                }
-                TokenKind::While => {
-                    self.currently_at += 1;
-                }
-                TokenKind::BoolLiteral => {
-                    self.currently_at += 1;
-                }
-                TokenKind::StringLiteral => {
-                    self.currently_at += 1;
-                }
-                TokenKind::IntLiteral => {
-                    self.currently_at += 1;
-                }
-                TokenKind::LongLiteral => {
-                    self.currently_at += 1;
-                }
-                TokenKind::FloatLiteral => {
-                    self.currently_at += 1;
-                }
-                TokenKind::DoubleLiteral => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Null => {
-                    self.currently_at += 1;
-                }
-                TokenKind::CharLiteral => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Nan => {
-                    self.currently_at += 1;
-                }
-                TokenKind::New => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Identifier => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Comma => {
-                    self.currently_at += 1;
-                }
-                TokenKind::OpeningBracket => {
-                    self.currently_at += 1;
-                }
-                TokenKind::ClosingBracket => {
-                    self.currently_at += 1;
-                }
-                TokenKind::OpeningBrace => {
-                    self.currently_at += 1;
-                }
-                TokenKind::ClosingBrace => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Asterisk => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Percent => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Assign => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Bang => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Tilde => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Plus => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Minus => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Slash => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Ampersand => {
-                    self.currently_at += 1;
-                }
-                TokenKind::LessThan => {
-                    self.currently_at += 1;
-                }
-                TokenKind::GreaterThan => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Caret => {
-                    self.currently_at += 1;
-                }
-                TokenKind::VerticalBar => {
-                    self.currently_at += 1;
-                }
-                TokenKind::Semicolon => {
-                    line_finished = true;
-                    self.currently_at += 1;
-                }
-                _ => {
-                    self.currently_at += 1;
-                }
+    /// Runs `parse`, and if it fails, skips to the next
+    /// statement and returns an `Error` node in place of
+    /// everything that was skipped
+    fn recovering(&mut self, parse: impl FnOnce(&mut Self) -> Option<MonkeyCStatement>) -> Option<MonkeyCStatement> {
+        let from = self.currently_at;
+        let error_count = self.errors.len();
+        match parse(self) {
+            Some(statement) => Some(statement),
+            None if self.errors.len() > error_count => Some(self.recover(from)),
+            None => None,
+        }
+    }
+
+    /// Skips tokens after a syntax error in a statement that starts
+    /// at token `from`, up to a point where parsing can continue:
+    /// past a `;`, or right before a `}` or a keyword that starts a
+    /// statement. Anything between `{` and `}` is skipped as a whole,
+    /// including brackets the statement opened before the error.
+    fn recover(&mut self, from: usize) -> MonkeyCStatement {
+        let mut depth: usize = 0;
+        for t in &self.token_list[from..self.currently_at.min(self.token_list.len())] {
+            match t.kind {
+                TokenKind::OpeningBracket => depth += 1,
+                TokenKind::ClosingBracket => depth = depth.saturating_sub(1),
+                _ => {}
             }
         }
-        if !errors.is_empty() {
-            return Err(errors);
+        // Always skip something, so that the same error isn't reported forever.
+        // A `{` is skipped by the loop below, which also keeps count of it.
+        if self.currently_at == from && !matches!(self.current_token().kind, TokenKind::Eof | TokenKind::OpeningBracket) {
+            self.currently_at += 1;
         }
-        Ok(statements)
+        loop {
+            match self.current_token().kind {
+                TokenKind::Eof => break,
+                TokenKind::OpeningBracket => depth += 1,
+                TokenKind::ClosingBracket if depth == 0 => break,
+                TokenKind::ClosingBracket => depth -= 1,
+                TokenKind::Semicolon if depth == 0 => {
+                    self.currently_at += 1;
+                    break;
+                }
+                ref kind if depth == 0 && starts_statement(kind) => break,
+                _ => {}
+            }
+            self.currently_at += 1;
+        }
+        let start = self.token_at(from).span;
+        let span = if self.currently_at > from { self.span_from(start) } else { Span::new(start.file_id, start.start, start.start) };
+        MonkeyCStatement::Error { span }
+    }
+
+    /// Parses the whole file. Malformed parts of it are
+    /// replaced with `Error` nodes, and the errors that
+    /// were found are returned together with the tree.
+    pub fn parse(&mut self) -> (Vec<MonkeyCStatement>, Vec<MCParseError>) {
+        let mut statements: Vec<MonkeyCStatement> = Vec::new();
+
+        while self.current_token().kind != TokenKind::Eof {
+            if self.current_token().kind == TokenKind::Semicolon {
+                self.currently_at += 1;
+                continue;
+            }
+            let statement = if self.at_declaration() {
+                self.recovering(Self::parse_declaration)
+            } else {
+                self.recovering(|parser| {
+                    parser.error_expected("a declaration");
+                    None
+                })
+            };
+            statements.extend(statement);
+        }
+        (statements, std::mem::take(&mut self.errors))
     }
 }
+
+/// Returns true for keywords that can only appear at
+/// the start of a statement, a declaration or a case
+fn starts_statement(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::Var | TokenKind::Const | TokenKind::Class | TokenKind::Function | TokenKind::Module |
+             TokenKind::Using | TokenKind::Import | TokenKind::Enum | TokenKind::Static | TokenKind::Hidden |
+             TokenKind::Private | TokenKind::Protected | TokenKind::Public | TokenKind::If | TokenKind::While |
+             TokenKind::Do | TokenKind::For | TokenKind::Switch | TokenKind::Return | TokenKind::Break |
+             TokenKind::Continue | TokenKind::Throw | TokenKind::Try | TokenKind::Case | TokenKind::Default)
+}
@@ -0,0 +1,814 @@
+use crate::config::{BraceStyle, FormatConfig};
+use crate::lexer::tokens::{Token, TriviaKind};
+use crate::parser::ast::{MonkeyCAnnotation, MonkeyCExprAssignmentOperation, MonkeyCExprBinaryOperation,
+                         MonkeyCExprBitwiseOperation, MonkeyCExprLogicalOperation, MonkeyCExprMathOperation,
+                         MonkeyCExprUnaryOperation, MonkeyCExprUpdateOperation, MonkeyCExpression, MonkeyCLiteralKind,
+                         MonkeyCModifier, MonkeyCParameter, MonkeyCStatement, MonkeyCTypeExpression,
+                         MonkeyCVariableDeclarator};
+use crate::span::Span;
+use std::collections::VecDeque;
+
+/// Precedence levels of expressions, from the loosest to
+/// the tightest. Binary operators follow the same order
+/// as in the parser.
+const ASSIGNMENT: u8 = 0;
+const TERNARY: u8 = 1;
+const LOGICAL_OR: u8 = 2;
+const LOGICAL_AND: u8 = 3;
+const COMPARISON: u8 = 4;
+const ADDITIVE: u8 = 5;
+const MULTIPLICATIVE: u8 = 6;
+const CAST: u8 = 7;
+const UNARY: u8 = 8;
+const POSTFIX: u8 = 9;
+const PRIMARY: u8 = 10;
+
+/// Turns a syntax tree back into canonically formatted code.
+/// Parsing the result gives the same tree, apart from spans.
+/// The only exception is an `if` without an `else` right before
+/// an `else` of another `if`, which gets wrapped into a block.
+pub fn print(statements: &[MonkeyCStatement]) -> String {
+    let config = FormatConfig::default();
+    let mut printer = Printer::new(&config, "", Vec::new());
+    printer.statements(statements);
+    printer.out
+}
+
+/// Prints a file the way `print` does, but laid out according to
+/// `config` and with the comments of `tokens` kept. Comments go
+/// before the statement that follows them, or after the one they
+/// end the line of. Comments in the middle of an expression are
+/// moved to the end of its statement. Blank lines of `source`
+/// between statements are kept, several of them become one.
+pub fn format(source: &str, tokens: &[Token], statements: &[MonkeyCStatement], config: &FormatConfig) -> String {
+    let comments = tokens.iter()
+        .flat_map(|token| token.leading_trivia.iter().chain(token.trailing_trivia.iter()))
+        .filter(|trivia| matches!(trivia.kind, TriviaKind::LineComment | TriviaKind::BlockComment))
+        .map(|trivia| Comment {
+            text: trivia.text.trim_end().replace("\r\n", "\n"),
+            span: trivia.span,
+            is_line: trivia.kind == TriviaKind::LineComment
+        })
+        .collect();
+    let mut printer = Printer::new(config, source, comments);
+    printer.statements(statements);
+    printer.comments_before(usize::MAX, false);
+    // The file keeps the kind of line breaks it starts with
+    if source.find('\n').is_some_and(|i| source[..i].ends_with('\r')) {
+        return printer.out.replace('\n', "\r\n");
+    }
+    printer.out
+}
+
+struct Comment {
+    text: String,
+    span: Span,
+    is_line: bool
+}
+
+struct Printer<'a> {
+    out: String,
+    /// Current nesting level
+    indent: usize,
+    config: &'a FormatConfig,
+    /// Code the tree was parsed from. It's empty when
+    /// there's no code, like in `print`.
+    source: &'a str,
+    /// Comments that weren't printed yet, in the order they
+    /// appear in the code
+    comments: VecDeque<Comment>,
+    /// Where the last statement or comment that
+    /// was printed ends in `source`
+    last_end: usize,
+    /// True at the start of the file, a block or a case,
+    /// where blank lines are never added
+    fresh: bool
+}
+
+impl<'a> Printer<'a> {
+    fn new(config: &'a FormatConfig, source: &'a str, comments: Vec<Comment>) -> Self {
+        Self { out: String::new(), indent: 0, config, source, comments: comments.into(), last_end: 0, fresh: true }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn write_indent(&mut self) {
+        let indent = self.indentation(self.indent);
+        self.out.push_str(&indent);
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+    }
+
+    fn indentation(&self, level: usize) -> String {
+        " ".repeat(level * self.config.indent_width)
+    }
+
+    /// Number of characters on the current line so far
+    fn column(&self) -> usize {
+        last_line_width(&self.out)
+    }
+
+    /// Adds a blank line before something that starts at `until`
+    /// if `force` is set or if there's one in the code. Code in
+    /// between, like the `else {` that a comment is moved past,
+    /// means that the blank line is somewhere else. Separators
+    /// like the `,` after an enum member don't count as code.
+    fn separate(&mut self, until: usize, force: bool) {
+        if self.fresh {
+            return;
+        }
+        let blank_in_source = self.source.get(self.last_end..until).is_some_and(|gap| {
+            gap.chars().all(|c| c.is_whitespace() || c == ',' || c == ';') && gap.matches('\n').count() > 1
+        });
+        if force || blank_in_source {
+            self.newline();
+        }
+    }
+
+    /// Prints the comments that start before `until`, each on
+    /// its own line. If `force` is set, they are separated from
+    /// what was printed before with a blank line. Returns true
+    /// if there were any.
+    fn comments_before(&mut self, until: usize, force: bool) -> bool {
+        let mut printed = false;
+        while self.comments.front().is_some_and(|comment| comment.span.start < until) {
+            let comment = self.comments.pop_front().unwrap();
+            self.separate(comment.span.start, force && !printed);
+            self.write_indent();
+            self.write(&comment.text);
+            self.newline();
+            self.last_end = comment.span.end;
+            self.fresh = false;
+            printed = true;
+        }
+        printed
+    }
+
+    /// Prints the comments inside of something that ends at `end`
+    /// and the ones after it on the same line at the end of the
+    /// current line. The `,` after an enum member doesn't count.
+    fn trailing_comments(&mut self, end: usize) {
+        self.last_end = self.last_end.max(end);
+        let mut after_line_comment = false;
+        while let Some(comment) = self.comments.front() {
+            let inside = comment.span.start < end;
+            let same_line = self.source.get(self.last_end..comment.span.start)
+                .is_some_and(|gap| gap.chars().all(|c| c == ',' || (c.is_whitespace() && c != '\n')));
+            if !inside && !same_line {
+                break;
+            }
+            let comment = self.comments.pop_front().unwrap();
+            // Nothing can follow a line comment on its line
+            if after_line_comment {
+                self.newline();
+                self.write_indent();
+            } else {
+                self.write(" ");
+            }
+            self.write(&comment.text);
+            self.last_end = self.last_end.max(comment.span.end);
+            after_line_comment = comment.is_line;
+        }
+    }
+
+    /// Prints every statement on its own line, and separates
+    /// classes, functions and modules with a blank line
+    fn statements(&mut self, statements: &[MonkeyCStatement]) {
+        for (i, statement) in statements.iter().enumerate() {
+            let span = statement.span();
+            let spaced = i > 0 && (is_spaced(&statements[i - 1]) || is_spaced(statement));
+            let commented = self.comments_before(span.start, spaced);
+            self.separate(span.start, spaced && !commented);
+            self.write_indent();
+            self.statement(statement);
+            self.fresh = false;
+            self.trailing_comments(span.end);
+            self.newline();
+        }
+    }
+
+    /// Prints a statement, starting at the current position.
+    /// Everything but the first line is indented.
+    fn statement(&mut self, statement: &MonkeyCStatement) {
+        match statement {
+            MonkeyCStatement::VariableDeclaration { declarators, is_const, annotations, modifiers, .. } => {
+                self.annotations(annotations);
+                let declaration = self.variable_declaration(declarators, *is_const, modifiers, self.column());
+                self.write(&declaration);
+                self.write(";");
+            }
+            MonkeyCStatement::ClassDeclaration { name, extends, annotations, modifiers, children, span } => {
+                self.annotations(annotations);
+                self.write(&format!("{}class {}", modifier_prefix(modifiers), name));
+                if let Some(extends) = extends {
+                    self.write(&format!(" extends {}", extends));
+                }
+                self.before_brace();
+                self.block(children, span.end);
+            }
+            MonkeyCStatement::FunctionDeclaration { name, params, return_type, annotations, modifiers, body, .. } => {
+                self.annotations(annotations);
+                let header = format!("{}function {}(", modifier_prefix(modifiers), name);
+                let return_type = return_type.as_ref().map(|t| format!(" as {}", type_expression(t))).unwrap_or_default();
+                let flat = parameters(params);
+                // Room for the ` {` after the header
+                if params.is_empty() || self.fits(self.column() + header.len() + flat.len() + return_type.len() + 3) {
+                    self.write(&format!("{}{})", header, flat));
+                } else {
+                    let items: Vec<String> = params.iter().map(|p| parameters(std::slice::from_ref(p))).collect();
+                    let list = self.split_list(&items, self.indent);
+                    self.write(&format!("{}{})", header, list));
+                }
+                self.write(&return_type);
+                self.before_brace();
+                self.statement(body);
+            }
+            MonkeyCStatement::ModuleDeclaration { name, annotations, modifiers, children, span } => {
+                self.annotations(annotations);
+                self.write(&format!("{}module {}", modifier_prefix(modifiers), name));
+                self.before_brace();
+                self.block(children, span.end);
+            }
+            MonkeyCStatement::Using { path, alias, annotations, modifiers, .. } => {
+                self.annotations(annotations);
+                self.write(&modifier_prefix(modifiers));
+                match alias {
+                    Some(alias) => self.write(&format!("using {} as {};", path, alias)),
+                    None => self.write(&format!("using {};", path)),
+                }
+            }
+            MonkeyCStatement::Import { path, annotations, modifiers, .. } => {
+                self.annotations(annotations);
+                self.write(&format!("{}import {};", modifier_prefix(modifiers), path));
+            }
+            MonkeyCStatement::EnumDeclaration { name, members, annotations, modifiers, span } => {
+                self.annotations(annotations);
+                self.write(&modifier_prefix(modifiers));
+                match name {
+                    Some(name) => self.write(&format!("enum {}", name)),
+                    None => self.write("enum"),
+                }
+                self.before_brace();
+                self.write("{");
+                if members.is_empty() && !self.has_comments_before(span.end) {
+                    self.write("}");
+                    return;
+                }
+                self.newline();
+                self.indent += 1;
+                self.fresh = true;
+                for (i, member) in members.iter().enumerate() {
+                    self.comments_before(member.span.start, false);
+                    self.separate(member.span.start, false);
+                    self.write_indent();
+                    self.write(&member.name);
+                    if let Some(value) = &member.value {
+                        self.write(&format!(" = {}", expression(value)));
+                    }
+                    if i + 1 < members.len() {
+                        self.write(",");
+                    }
+                    self.fresh = false;
+                    self.trailing_comments(member.span.end);
+                    self.newline();
+                }
+                self.comments_before(span.end, false);
+                self.indent -= 1;
+                self.write_indent();
+                self.write("}");
+            }
+            MonkeyCStatement::ExpressionStatement { expression, .. } => {
+                let statement = self.expression_statement(expression, self.column());
+                self.write(&statement);
+                self.write(";");
+            }
+            // Error nodes have no code of their own
+            MonkeyCStatement::Empty { .. } | MonkeyCStatement::Error { .. } => self.write(";"),
+            MonkeyCStatement::Block { children, span } => self.block(children, span.end),
+            MonkeyCStatement::If { condition, then_branch, else_branch, .. } => {
+                self.write(&format!("if ({})", expression(condition)));
+                match else_branch {
+                    Some(else_branch) => {
+                        // An `else` would go to the inner `if` otherwise
+                        if ends_with_open_if(then_branch) {
+                            self.before_brace();
+                            self.write("{");
+                            self.newline();
+                            self.indent += 1;
+                            self.write_indent();
+                            self.statement(then_branch);
+                            self.newline();
+                            self.indent -= 1;
+                            self.write_indent();
+                            self.write("}");
+                            self.after_brace("else");
+                        } else {
+                            self.branch(then_branch);
+                            self.continuation(then_branch, "else");
+                        }
+                        if let MonkeyCStatement::If { .. } = else_branch.as_ref() {
+                            self.write(" ");
+                            self.statement(else_branch);
+                        } else {
+                            self.branch(else_branch);
+                        }
+                    }
+                    None => self.branch(then_branch),
+                }
+            }
+            MonkeyCStatement::While { condition, body, .. } => {
+                self.write(&format!("while ({})", expression(condition)));
+                self.branch(body);
+            }
+            MonkeyCStatement::DoWhile { body, condition, .. } => {
+                self.write("do");
+                self.branch(body);
+                self.continuation(body, &format!("while ({});", expression(condition)));
+            }
+            MonkeyCStatement::For { init, condition, update, body, .. } => {
+                let used = self.column() + "for (".len();
+                let init = match init.as_deref() {
+                    Some(MonkeyCStatement::VariableDeclaration { declarators, is_const, modifiers, .. }) =>
+                        self.variable_declaration(declarators, *is_const, modifiers, used),
+                    Some(MonkeyCStatement::ExpressionStatement { expression, .. }) => self.expression_statement(expression, used),
+                    _ => String::new(),
+                };
+                let condition = condition.as_ref().map(expression).map(|c| format!(" {}", c)).unwrap_or_default();
+                let update = update.as_ref().map(expression).map(|u| format!(" {}", u)).unwrap_or_default();
+                self.write(&format!("for ({};{};{})", init, condition, update));
+                self.branch(body);
+            }
+            MonkeyCStatement::Switch { value, cases, span } => {
+                self.write(&format!("switch ({})", expression(value)));
+                self.before_brace();
+                self.write("{");
+                self.newline();
+                self.indent += 1;
+                self.fresh = true;
+                for case in cases {
+                    self.comments_before(case.span.start, false);
+                    self.separate(case.span.start, false);
+                    self.write_indent();
+                    match &case.value {
+                        // `?:` of a nullable type would be taken as one token
+                        Some(value) => self.write(&format!("case {}:", left_operand(value, TERNARY))),
+                        None => self.write("default:"),
+                    }
+                    self.newline();
+                    self.indent += 1;
+                    self.fresh = true;
+                    self.statements(&case.children);
+                    self.indent -= 1;
+                    self.fresh = false;
+                }
+                self.comments_before(span.end, false);
+                self.indent -= 1;
+                self.write_indent();
+                self.write("}");
+            }
+            MonkeyCStatement::Break { .. } => self.write("break;"),
+            MonkeyCStatement::Continue { .. } => self.write("continue;"),
+            MonkeyCStatement::Return { value, .. } => match value {
+                Some(value) => {
+                    let value = self.fitted(value, ASSIGNMENT, self.column() + "return ".len(), self.indent);
+                    self.write(&format!("return {};", value));
+                }
+                None => self.write("return;"),
+            },
+            MonkeyCStatement::Throw { value, .. } => {
+                let value = self.fitted(value, ASSIGNMENT, self.column() + "throw ".len(), self.indent);
+                self.write(&format!("throw {};", value));
+            }
+            MonkeyCStatement::Try { body, catches, finally, .. } => {
+                self.write("try");
+                self.before_brace();
+                self.statement(body);
+                for catch in catches {
+                    match &catch.class {
+                        Some(class) => self.after_brace(&format!("catch ({} instanceof {})", catch.binding, class)),
+                        None => self.after_brace(&format!("catch ({})", catch.binding)),
+                    }
+                    self.before_brace();
+                    self.statement(&catch.body);
+                }
+                if let Some(finally) = finally {
+                    self.after_brace("finally");
+                    self.before_brace();
+                    self.statement(finally);
+                }
+            }
+        }
+    }
+
+    /// Prints the annotations on a line of their own
+    fn annotations(&mut self, annotations: &[MonkeyCAnnotation]) {
+        if annotations.is_empty() {
+            return;
+        }
+        let annotations: Vec<String> = annotations.iter().map(|a| if a.args.is_empty() {
+            format!(":{}", a.name)
+        } else {
+            format!(":{}({})", a.name, expression_list(&a.args))
+        }).collect();
+        self.write(&format!("({})", annotations.join(", ")));
+        self.newline();
+        self.write_indent();
+    }
+
+    fn has_comments_before(&self, until: usize) -> bool {
+        self.comments.front().is_some_and(|comment| comment.span.start < until)
+    }
+
+    /// Prints `{`, the statements indented, and `}`.
+    /// `end` is where the block ends in the code.
+    fn block(&mut self, children: &[MonkeyCStatement], end: usize) {
+        self.write("{");
+        if children.is_empty() && !self.has_comments_before(end) {
+            self.write("}");
+            return;
+        }
+        self.newline();
+        self.indent += 1;
+        self.fresh = true;
+        self.statements(children);
+        self.comments_before(end, false);
+        self.indent -= 1;
+        self.write_indent();
+        self.write("}");
+    }
+
+    /// Goes to where a `{` that follows something is printed
+    fn before_brace(&mut self) {
+        match self.config.brace_style {
+            BraceStyle::SameLine => self.write(" "),
+            BraceStyle::NextLine => {
+                self.newline();
+                self.write_indent();
+            }
+        }
+    }
+
+    /// Prints `text` that follows a `}`, like `else`
+    fn after_brace(&mut self, text: &str) {
+        match self.config.brace_style {
+            BraceStyle::SameLine => self.write(" "),
+            BraceStyle::NextLine => {
+                self.newline();
+                self.write_indent();
+            }
+        }
+        self.write(text);
+    }
+
+    /// Prints the body of `if`, `while` and such. Blocks
+    /// start where the brace style says, other statements
+    /// go on the next line and are indented.
+    fn branch(&mut self, body: &MonkeyCStatement) {
+        if let MonkeyCStatement::Block { children, span } = body {
+            self.before_brace();
+            self.block(children, span.end);
+            return;
+        }
+        self.newline();
+        self.indent += 1;
+        self.write_indent();
+        self.statement(body);
+        self.indent -= 1;
+    }
+
+    /// Prints the part that comes after a branch, like `else`.
+    /// It goes after a `}`, or on a line of its own.
+    fn continuation(&mut self, branch: &MonkeyCStatement, text: &str) {
+        if let MonkeyCStatement::Block { .. } = branch {
+            self.after_brace(text);
+        } else {
+            self.newline();
+            self.write_indent();
+            self.write(text);
+        }
+    }
+
+    /// Returns true if a line of `width` characters, with
+    /// room for a `;` or a `,` at its end, isn't too long
+    fn fits(&self, width: usize) -> bool {
+        width < self.config.max_line_width
+    }
+
+    /// A variable declaration without the `;`, that
+    /// starts `used` characters into the line
+    fn variable_declaration(&self, declarators: &[MonkeyCVariableDeclarator], is_const: bool,
+                            modifiers: &[MonkeyCModifier], used: usize) -> String {
+        let keyword = if is_const { "const" } else { "var" };
+        let mut text = format!("{}{} ", modifier_prefix(modifiers), keyword);
+        for (i, d) in declarators.iter().enumerate() {
+            if i > 0 {
+                text.push_str(", ");
+            }
+            text.push_str(&d.name);
+            if let Some(var_type) = &d.var_type {
+                text.push_str(&format!(" as {}", type_expression(var_type)));
+            }
+            if let Some(default_val) = &d.default_val {
+                text.push_str(" = ");
+                let used = if text.contains('\n') { last_line_width(&text) } else { used + text.len() };
+                text.push_str(&self.fitted(default_val, ASSIGNMENT, used, self.indent));
+            }
+        }
+        text
+    }
+
+    /// An expression that a statement consists of, without
+    /// the `;`, that starts `used` characters into the line
+    fn expression_statement(&self, expression: &MonkeyCExpression, used: usize) -> String {
+        // A `{` would start a block instead of a dictionary
+        let wrap = |text: String| if text.starts_with('{') { format!("({})", text) } else { text };
+        match expression {
+            // Assignments can't be in brackets themselves
+            MonkeyCExpression::Assignment { target, op, value, .. } => {
+                let target = format!("{} {} ", wrap(operand(target, POSTFIX)), assignment_operator(op));
+                let value = self.fitted(value, TERNARY, used + target.len(), self.indent);
+                format!("{}{}", target, value)
+            }
+            _ => wrap(self.fitted(expression, ASSIGNMENT, used, self.indent)),
+        }
+    }
+
+    /// Prints `expression` like `operand` does. If it doesn't fit into the
+    /// line after `used` characters, and it's a call, an array or such,
+    /// its items go on lines of their own, one nesting level deeper than
+    /// `level`. The items themselves are split the same way.
+    fn fitted(&self, expression: &MonkeyCExpression, min_precedence: u8, used: usize, level: usize) -> String {
+        let flat = operand(expression, min_precedence);
+        if self.fits(used + flat.len()) || precedence(expression) < min_precedence {
+            return flat;
+        }
+        let item_used = (level + 1) * self.config.indent_width;
+        let items = |list: &[MonkeyCExpression]| -> Vec<String> {
+            list.iter().map(|e| self.fitted(e, TERNARY, item_used, level + 1)).collect()
+        };
+        match expression {
+            MonkeyCExpression::Call { callee, args, .. } if !args.is_empty() =>
+                format!("{}({})", operand(callee, POSTFIX), self.split_list(&items(args), level)),
+            MonkeyCExpression::New { class, args, .. } if !args.is_empty() =>
+                format!("new {}({})", self::expression(class), self.split_list(&items(args), level)),
+            MonkeyCExpression::Array { elements, is_byte_array, .. } if !elements.is_empty() =>
+                format!("[{}]{}", self.split_list(&items(elements), level), if *is_byte_array { "b" } else { "" }),
+            MonkeyCExpression::Dictionary { entries, .. } if !entries.is_empty() => {
+                let entries: Vec<String> = entries.iter().map(|(key, value)| {
+                    let key = format!("{} => ", operand(key, TERNARY));
+                    let value = self.fitted(value, TERNARY, item_used + key.len(), level + 1);
+                    format!("{}{}", key, value)
+                }).collect();
+                format!("{{{}}}", self.split_list(&entries, level))
+            }
+            MonkeyCExpression::Assignment { target, op, value, .. } => {
+                let target = format!("{} {} ", operand(target, POSTFIX), assignment_operator(op));
+                let value = self.fitted(value, TERNARY, used + target.len(), level);
+                format!("{}{}", target, value)
+            }
+            _ => flat,
+        }
+    }
+
+    /// Puts `items` on lines of their own, one nesting level
+    /// deeper than `level`, for the inside of some brackets
+    fn split_list(&self, items: &[String], level: usize) -> String {
+        let indentation = self.indentation(level + 1);
+        let items: Vec<String> = items.iter().map(|item| format!("{}{}", indentation, item)).collect();
+        format!("\n{}\n{}", items.join(",\n"), self.indentation(level))
+    }
+}
+
+/// Number of characters after the last line break of `text`
+fn last_line_width(text: &str) -> usize {
+    text.rsplit('\n').next().map_or(0, |line| line.chars().count())
+}
+
+/// Classes, functions and modules are separated from
+/// the statements around them with a blank line
+fn is_spaced(statement: &MonkeyCStatement) -> bool {
+    matches!(statement, MonkeyCStatement::ClassDeclaration { .. } | MonkeyCStatement::FunctionDeclaration { .. } |
+             MonkeyCStatement::ModuleDeclaration { .. })
+}
+
+/// Returns true if an `else` right after `statement`
+/// would belong to an `if` inside of it
+fn ends_with_open_if(statement: &MonkeyCStatement) -> bool {
+    match statement {
+        MonkeyCStatement::If { else_branch: None, .. } => true,
+        MonkeyCStatement::If { else_branch: Some(last), .. } |
+        MonkeyCStatement::While { body: last, .. } |
+        MonkeyCStatement::For { body: last, .. } => ends_with_open_if(last),
+        _ => false,
+    }
+}
+
+fn modifier_prefix(modifiers: &[MonkeyCModifier]) -> String {
+    modifiers.iter().map(|m| match m {
+        MonkeyCModifier::Static => "static ",
+        MonkeyCModifier::Hidden => "hidden ",
+        MonkeyCModifier::Private => "private ",
+        MonkeyCModifier::Protected => "protected ",
+        MonkeyCModifier::Public => "public ",
+    }).collect()
+}
+
+fn assignment_operator(op: &MonkeyCExprAssignmentOperation) -> &'static str {
+    match op {
+        MonkeyCExprAssignmentOperation::Assign => "=",
+        MonkeyCExprAssignmentOperation::Add => "+=",
+        MonkeyCExprAssignmentOperation::Subtract => "-=",
+        MonkeyCExprAssignmentOperation::Multiply => "*=",
+        MonkeyCExprAssignmentOperation::Divide => "/=",
+        MonkeyCExprAssignmentOperation::Modulo => "%=",
+        MonkeyCExprAssignmentOperation::BitwiseAnd => "&=",
+        MonkeyCExprAssignmentOperation::BitwiseOr => "|=",
+        MonkeyCExprAssignmentOperation::BitwiseXor => "^=",
+        MonkeyCExprAssignmentOperation::LeftShift => "<<=",
+        MonkeyCExprAssignmentOperation::RightShift => ">>=",
+    }
+}
+
+fn parameters(params: &[MonkeyCParameter]) -> String {
+    params.iter().map(|p| match &p.param_type {
+        Some(param_type) => format!("{} as {}", p.name, type_expression(param_type)),
+        None => p.name.clone(),
+    }).collect::<Vec<_>>().join(", ")
+}
+
+pub fn type_expression(type_expression: &MonkeyCTypeExpression) -> String {
+    match type_expression {
+        MonkeyCTypeExpression::Named { name, generics, .. } if generics.is_empty() => name.clone(),
+        MonkeyCTypeExpression::Named { name, generics, .. } =>
+            format!("{}<{}>", name, generics.iter().map(self::type_expression).collect::<Vec<_>>().join(", ")),
+        MonkeyCTypeExpression::Union { types, .. } =>
+            types.iter().map(self::type_expression).collect::<Vec<_>>().join(" or "),
+        MonkeyCTypeExpression::Nullable { inner, .. } => format!("{}?", self::type_expression(inner)),
+        MonkeyCTypeExpression::Callback { params, return_type, .. } => match return_type {
+            Some(return_type) => format!("Method({}) as {}", parameters(params), self::type_expression(return_type)),
+            None => format!("Method({})", parameters(params)),
+        },
+        MonkeyCTypeExpression::DictionaryShape { entries, .. } => format!("{{{}}}", entries.iter()
+            .map(|(key, value)| format!("{} as {}", expression(key), self::type_expression(value)))
+            .collect::<Vec<_>>().join(", ")),
+        MonkeyCTypeExpression::Tuple { types, .. } =>
+            format!("[{}]", types.iter().map(self::type_expression).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+fn precedence(expression: &MonkeyCExpression) -> u8 {
+    match expression {
+        MonkeyCExpression::Assignment { .. } => ASSIGNMENT,
+        MonkeyCExpression::Ternary { .. } | MonkeyCExpression::Elvis { .. } => TERNARY,
+        MonkeyCExpression::Logical { op: MonkeyCExprLogicalOperation::Or, .. } => LOGICAL_OR,
+        MonkeyCExpression::Logical { op: MonkeyCExprLogicalOperation::And, .. } => LOGICAL_AND,
+        MonkeyCExpression::Binary { .. } | MonkeyCExpression::InstanceOf { .. } | MonkeyCExpression::Has { .. } => COMPARISON,
+        MonkeyCExpression::Mathematical { op, .. } => match op {
+            MonkeyCExprMathOperation::Add | MonkeyCExprMathOperation::Subtract => ADDITIVE,
+            _ => MULTIPLICATIVE,
+        },
+        MonkeyCExpression::Bitwise { op, .. } => match op {
+            MonkeyCExprBitwiseOperation::Or | MonkeyCExprBitwiseOperation::Xor => ADDITIVE,
+            _ => MULTIPLICATIVE,
+        },
+        MonkeyCExpression::Cast { .. } => CAST,
+        MonkeyCExpression::Unary { .. } | MonkeyCExpression::Update { is_prefix: true, .. } => UNARY,
+        MonkeyCExpression::Update { .. } | MonkeyCExpression::Member { .. } | MonkeyCExpression::Index { .. } |
+        MonkeyCExpression::Call { .. } => POSTFIX,
+        _ => PRIMARY,
+    }
+}
+
+/// Prints `expression`, in brackets if it binds looser than `min_precedence`
+fn operand(expression: &MonkeyCExpression, min_precedence: u8) -> String {
+    if precedence(expression) < min_precedence {
+        format!("({})", self::expression(expression))
+    } else {
+        self::expression(expression)
+    }
+}
+
+/// Like `operand`, but for the left side of an operator. If the
+/// expression ends with a cast, it's always in brackets, since
+/// the operator could be taken as a part of the type otherwise,
+/// like in `x as Number < y`.
+fn left_operand(expression: &MonkeyCExpression, min_precedence: u8) -> String {
+    if ends_with_cast(expression) {
+        format!("({})", self::expression(expression))
+    } else {
+        operand(expression, min_precedence)
+    }
+}
+
+/// Returns true if the printed expression ends with a type
+fn ends_with_cast(expression: &MonkeyCExpression) -> bool {
+    match expression {
+        MonkeyCExpression::Cast { .. } => true,
+        MonkeyCExpression::Mathematical { rhs, .. } | MonkeyCExpression::Binary { rhs, .. } |
+        MonkeyCExpression::Bitwise { rhs, .. } | MonkeyCExpression::Logical { rhs, .. } |
+        MonkeyCExpression::InstanceOf { class: rhs, .. } | MonkeyCExpression::Has { member: rhs, .. } =>
+            precedence(rhs) > precedence(expression) && ends_with_cast(rhs),
+        MonkeyCExpression::Ternary { if_false: rhs, .. } | MonkeyCExpression::Elvis { rhs, .. } |
+        MonkeyCExpression::Assignment { value: rhs, .. } => precedence(rhs) >= TERNARY && ends_with_cast(rhs),
+        _ => false,
+    }
+}
+
+fn expression_list(expressions: &[MonkeyCExpression]) -> String {
+    expressions.iter().map(|e| operand(e, TERNARY)).collect::<Vec<_>>().join(", ")
+}
+
+pub fn expression(expression: &MonkeyCExpression) -> String {
+    // All binary operators are left-associative
+    let binary = |lhs: &MonkeyCExpression, op: &str, rhs: &MonkeyCExpression| {
+        let level = precedence(expression);
+        format!("{} {} {}", left_operand(lhs, level), op, operand(rhs, level + 1))
+    };
+    match expression {
+        MonkeyCExpression::Simple { value, kind, .. } => match kind {
+            MonkeyCLiteralKind::String => format!("\"{}\"", value),
+            MonkeyCLiteralKind::Char => format!("'{}'", value),
+            MonkeyCLiteralKind::Symbol => format!(":{}", value),
+            _ => value.clone(),
+        },
+        MonkeyCExpression::Reference { name, .. } => name.clone(),
+        MonkeyCExpression::Mathematical { lhs, op, rhs, .. } => binary(lhs, match op {
+            MonkeyCExprMathOperation::Add => "+",
+            MonkeyCExprMathOperation::Subtract => "-",
+            MonkeyCExprMathOperation::Divide => "/",
+            MonkeyCExprMathOperation::Multiply => "*",
+            MonkeyCExprMathOperation::Modulo => "%",
+        }, rhs),
+        MonkeyCExpression::Binary { lhs, op, rhs, .. } => binary(lhs, match op {
+            MonkeyCExprBinaryOperation::LessThan => "<",
+            MonkeyCExprBinaryOperation::LessThanEquals => "<=",
+            MonkeyCExprBinaryOperation::GreaterThan => ">",
+            MonkeyCExprBinaryOperation::GreaterThanEquals => ">=",
+            MonkeyCExprBinaryOperation::Equals => "==",
+            MonkeyCExprBinaryOperation::NotEquals => "!=",
+        }, rhs),
+        MonkeyCExpression::Bitwise { lhs, op, rhs, .. } => binary(lhs, match op {
+            MonkeyCExprBitwiseOperation::LeftShift => "<<",
+            MonkeyCExprBitwiseOperation::RightShift => ">>",
+            MonkeyCExprBitwiseOperation::And => "&",
+            MonkeyCExprBitwiseOperation::Or => "|",
+            MonkeyCExprBitwiseOperation::Xor => "^",
+        }, rhs),
+        MonkeyCExpression::Logical { lhs, op, rhs, .. } => binary(lhs, match op {
+            MonkeyCExprLogicalOperation::And => "&&",
+            MonkeyCExprLogicalOperation::Or => "||",
+        }, rhs),
+        MonkeyCExpression::InstanceOf { value, class, .. } => binary(value, "instanceof", class),
+        MonkeyCExpression::Has { value, member, .. } => binary(value, "has", member),
+        MonkeyCExpression::Unary { op, operand: inner, .. } => {
+            let op = match op {
+                MonkeyCExprUnaryOperation::Negate => "-",
+                MonkeyCExprUnaryOperation::Not => "!",
+                MonkeyCExprUnaryOperation::BitwiseNot => "~",
+            };
+            prefix(op, &operand(inner, UNARY))
+        }
+        MonkeyCExpression::Update { op, operand: inner, is_prefix, .. } => {
+            let op = match op {
+                MonkeyCExprUpdateOperation::Increment => "++",
+                MonkeyCExprUpdateOperation::Decrement => "--",
+            };
+            if *is_prefix {
+                prefix(op, &operand(inner, UNARY))
+            } else {
+                format!("{}{}", operand(inner, POSTFIX), op)
+            }
+        }
+        MonkeyCExpression::Ternary { condition, if_true, if_false, .. } =>
+            format!("{} ? {} : {}", left_operand(condition, LOGICAL_OR), operand(if_true, TERNARY), operand(if_false, TERNARY)),
+        MonkeyCExpression::Elvis { lhs, rhs, .. } => format!("{} ?: {}", left_operand(lhs, LOGICAL_OR), operand(rhs, TERNARY)),
+        MonkeyCExpression::Assignment { target, op, value, .. } =>
+            format!("{} {} {}", operand(target, POSTFIX), assignment_operator(op), operand(value, TERNARY)),
+        MonkeyCExpression::Cast { value, target_type, .. } => format!("{} as {}", operand(value, CAST), type_expression(target_type)),
+        MonkeyCExpression::Member { object, name, .. } => format!("{}.{}", operand(object, POSTFIX), name),
+        MonkeyCExpression::Index { object, index, .. } => format!("{}[{}]", operand(object, POSTFIX), operand(index, TERNARY)),
+        MonkeyCExpression::Call { callee, args, .. } => format!("{}({})", operand(callee, POSTFIX), expression_list(args)),
+        MonkeyCExpression::New { class, args, .. } => format!("new {}({})", self::expression(class), expression_list(args)),
+        MonkeyCExpression::NewArray { size, is_byte_array, .. } =>
+            format!("new [{}]{}", operand(size, TERNARY), if *is_byte_array { "b" } else { "" }),
+        MonkeyCExpression::Array { elements, is_byte_array, .. } =>
+            format!("[{}]{}", expression_list(elements), if *is_byte_array { "b" } else { "" }),
+        MonkeyCExpression::Dictionary { entries, .. } => format!("{{{}}}", entries.iter()
+            .map(|(key, value)| format!("{} => {}", operand(key, TERNARY), operand(value, TERNARY)))
+            .collect::<Vec<_>>().join(", ")),
+    }
+}
+
+/// Puts a prefix operator in front of `operand`, with a
+/// space if they would be lexed as one token otherwise
+fn prefix(op: &str, operand: &str) -> String {
+    if op.ends_with('-') && operand.starts_with('-') {
+        format!("{} {}", op, operand)
+    } else {
+        format!("{}{}", op, operand)
+    }
+}
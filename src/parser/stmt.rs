@@ -0,0 +1,238 @@
+use crate::lexer::tokens::TokenKind;
+use crate::parser::ast::{MonkeyCCatch, MonkeyCExpression, MonkeyCStatement, MonkeyCSwitchCase};
+use crate::parser::MonkeyCParser;
+
+impl MonkeyCParser {
+    /// Parses statements between `{` and `}`,
+    /// starting at the opening bracket
+    pub(super) fn parse_block(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.expect(TokenKind::OpeningBracket, "'{'")?.span;
+        let mut children = Vec::new();
+        loop {
+            match self.current_token().kind {
+                TokenKind::ClosingBracket => break,
+                TokenKind::Semicolon => self.currently_at += 1,
+                TokenKind::Eof => {
+                    // Everything up to here is still worth keeping
+                    self.error_expected("'}'");
+                    return Some(MonkeyCStatement::Block { children, span: self.span_from(start) });
+                }
+                _ => children.extend(self.recovering(Self::parse_statement)),
+            }
+        }
+        self.currently_at += 1;
+        Some(MonkeyCStatement::Block { children, span: self.span_from(start) })
+    }
+
+    /// Parses a statement inside of a function. Returns `None`
+    /// if the statement is malformed, in which case the error
+    /// is already recorded.
+    fn parse_statement(&mut self) -> Option<MonkeyCStatement> {
+        self.nested("Statement", Self::parse_statement_inner)
+    }
+
+    fn parse_statement_inner(&mut self) -> Option<MonkeyCStatement> {
+        let t = self.current_token();
+        let start = t.span;
+        match t.kind {
+            TokenKind::Eof | TokenKind::ClosingBracket => {
+                self.error_expected("a statement");
+                None
+            }
+            TokenKind::OpeningBracket => self.parse_block(),
+            TokenKind::Semicolon => {
+                self.currently_at += 1;
+                Some(MonkeyCStatement::Empty { span: t.span })
+            }
+            TokenKind::If => self.parse_if(),
+            TokenKind::While => self.parse_while(),
+            TokenKind::Do => self.parse_do_while(),
+            TokenKind::For => self.parse_for(),
+            TokenKind::Switch => self.parse_switch(),
+            TokenKind::Break | TokenKind::Continue => {
+                self.currently_at += 1;
+                self.expect(TokenKind::Semicolon, "';'")?;
+                let span = self.span_from(start);
+                Some(if t.kind == TokenKind::Break {
+                    MonkeyCStatement::Break { span }
+                } else {
+                    MonkeyCStatement::Continue { span }
+                })
+            }
+            TokenKind::Return => self.parse_return(),
+            TokenKind::Throw => self.parse_throw(),
+            TokenKind::Try => self.parse_try(),
+            // Annotations aren't allowed inside of functions, so `(` starts an expression
+            TokenKind::OpeningBrace => self.parse_expression_statement(),
+            _ if self.at_declaration() => self.parse_declaration(),
+            _ => self.parse_expression_statement(),
+        }
+    }
+
+    fn parse_if(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let condition = self.parse_condition()?;
+        let then_branch = Box::new(self.parse_statement()?);
+        let mut else_branch = None;
+        if self.current_token().kind == TokenKind::Else {
+            self.currently_at += 1;
+            else_branch = Some(Box::new(self.parse_statement()?));
+        }
+        Some(MonkeyCStatement::If { condition, then_branch, else_branch, span: self.span_from(start) })
+    }
+
+    fn parse_while(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let condition = self.parse_condition()?;
+        let body = Box::new(self.parse_statement()?);
+        Some(MonkeyCStatement::While { condition, body, span: self.span_from(start) })
+    }
+
+    fn parse_do_while(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let body = Box::new(self.parse_statement()?);
+        self.expect(TokenKind::While, "'while'")?;
+        let condition = self.parse_condition()?;
+        self.expect(TokenKind::Semicolon, "';'")?;
+        Some(MonkeyCStatement::DoWhile { body, condition, span: self.span_from(start) })
+    }
+
+    fn parse_return(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let mut value = None;
+        if self.current_token().kind != TokenKind::Semicolon {
+            value = Some(self.parse_expression()?);
+        }
+        self.expect(TokenKind::Semicolon, "';'")?;
+        Some(MonkeyCStatement::Return { value, span: self.span_from(start) })
+    }
+
+    fn parse_throw(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let value = self.parse_expression()?;
+        self.expect(TokenKind::Semicolon, "';'")?;
+        Some(MonkeyCStatement::Throw { value, span: self.span_from(start) })
+    }
+
+    /// Parses `foo = bar + 1;`, `i++;`, `view.requestUpdate();` and such
+    fn parse_expression_statement(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        let expression = self.parse_assignment()?;
+        self.expect(TokenKind::Semicolon, "';'")?;
+        Some(MonkeyCStatement::ExpressionStatement { expression, span: self.span_from(start) })
+    }
+
+    /// Parses `(condition)` of `if`, `while` and such
+    fn parse_condition(&mut self) -> Option<MonkeyCExpression> {
+        self.expect(TokenKind::OpeningBrace, "'('")?;
+        let condition = self.parse_expression()?;
+        self.expect(TokenKind::ClosingBrace, "')'")?;
+        Some(condition)
+    }
+
+    fn parse_for(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        self.expect(TokenKind::OpeningBrace, "'('")?;
+
+        // Both kinds of the initializer take the semicolon after them too
+        let init = match self.current_token().kind {
+            TokenKind::Var => Some(Box::new(self.parse_declaration()?)),
+            TokenKind::Semicolon => {
+                self.currently_at += 1;
+                None
+            }
+            _ => Some(Box::new(self.parse_expression_statement()?)),
+        };
+        let mut condition = None;
+        if self.current_token().kind != TokenKind::Semicolon {
+            condition = Some(self.parse_expression()?);
+        }
+        self.expect(TokenKind::Semicolon, "';'")?;
+        let mut update = None;
+        if self.current_token().kind != TokenKind::ClosingBrace {
+            update = Some(self.parse_assignment()?);
+        }
+        self.expect(TokenKind::ClosingBrace, "')'")?;
+
+        let body = Box::new(self.parse_statement()?);
+        Some(MonkeyCStatement::For { init, condition, update, body, span: self.span_from(start) })
+    }
+
+    fn parse_switch(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let value = self.parse_condition()?;
+        self.expect(TokenKind::OpeningBracket, "'{'")?;
+
+        let mut cases = Vec::new();
+        while self.current_token().kind != TokenKind::ClosingBracket {
+            let case_start = self.current_token().span;
+            let case_value = match self.current_token().kind {
+                TokenKind::Case => {
+                    self.currently_at += 1;
+                    Some(self.parse_expression()?)
+                }
+                TokenKind::Default => {
+                    self.currently_at += 1;
+                    None
+                }
+                _ => {
+                    self.error_expected("'case' or 'default'");
+                    return None;
+                }
+            };
+            self.expect(TokenKind::Colon, "':'")?;
+
+            let mut children = Vec::new();
+            loop {
+                match self.current_token().kind {
+                    TokenKind::Case | TokenKind::Default | TokenKind::ClosingBracket | TokenKind::Eof => break,
+                    TokenKind::Semicolon => self.currently_at += 1,
+                    _ => children.extend(self.recovering(Self::parse_statement)),
+                }
+            }
+            cases.push(MonkeyCSwitchCase { value: case_value, children, span: self.span_from(case_start) });
+        }
+        self.currently_at += 1;
+        Some(MonkeyCStatement::Switch { value, cases, span: self.span_from(start) })
+    }
+
+    fn parse_try(&mut self) -> Option<MonkeyCStatement> {
+        let start = self.current_token().span;
+        self.currently_at += 1;
+        let body = Box::new(self.parse_block()?);
+
+        let mut catches = Vec::new();
+        while self.current_token().kind == TokenKind::Catch {
+            let catch_start = self.current_token().span;
+            self.currently_at += 1;
+            self.expect(TokenKind::OpeningBrace, "'('")?;
+            let binding = self.expect(TokenKind::Identifier, "an identifier")?.literal;
+            let mut class = None;
+            if self.current_token().kind == TokenKind::InstanceOf {
+                self.currently_at += 1;
+                class = Some(self.parse_qualified_name()?);
+            }
+            self.expect(TokenKind::ClosingBrace, "')'")?;
+            let body = Box::new(self.parse_block()?);
+            catches.push(MonkeyCCatch { binding, class, body, span: self.span_from(catch_start) });
+        }
+
+        let mut finally = None;
+        if self.current_token().kind == TokenKind::Finally {
+            self.currently_at += 1;
+            finally = Some(Box::new(self.parse_block()?));
+        }
+        if catches.is_empty() && finally.is_none() {
+            self.error_expected("'catch' or 'finally'");
+            return None;
+        }
+        Some(MonkeyCStatement::Try { body, catches, finally, span: self.span_from(start) })
+    }
+}
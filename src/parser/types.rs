@@ -0,0 +1,154 @@
+use crate::lexer::tokens::{Token, TokenKind};
+use crate::parser::ast::{MonkeyCExpression, MonkeyCLiteralKind, MonkeyCTypeExpression};
+use crate::parser::expr::starts_expression;
+use crate::parser::MonkeyCParser;
+use crate::span::Span;
+
+impl MonkeyCParser {
+    /// Parses a type, like `Array<Number> or Null`
+    pub(super) fn parse_type(&mut self) -> Option<MonkeyCTypeExpression> {
+        self.parse_union(false)
+    }
+
+    /// Parses the type after `as` in an expression. A `?` that is
+    /// followed by an expression starts a ternary there, like in
+    /// `x as Boolean ? 1 : 2`, instead of making the type nullable.
+    pub(super) fn parse_cast_type(&mut self) -> Option<MonkeyCTypeExpression> {
+        self.parse_union(true)
+    }
+
+    fn parse_union(&mut self, in_expression: bool) -> Option<MonkeyCTypeExpression> {
+        let start = self.current_token().span;
+        let first = self.parse_single_type(in_expression)?;
+        if self.current_token().kind != TokenKind::Or {
+            return Some(first);
+        }
+        let mut types = vec![first];
+        while self.current_token().kind == TokenKind::Or {
+            self.currently_at += 1;
+            types.push(self.parse_single_type(in_expression)?);
+        }
+        Some(MonkeyCTypeExpression::Union { types, span: self.span_from(start) })
+    }
+
+    /// Parses a type that isn't a union
+    fn parse_single_type(&mut self, in_expression: bool) -> Option<MonkeyCTypeExpression> {
+        self.nested("Type", |parser| parser.parse_single_type_inner(in_expression))
+    }
+
+    fn parse_single_type_inner(&mut self, in_expression: bool) -> Option<MonkeyCTypeExpression> {
+        let t = self.current_token();
+        let mut single = match t.kind {
+            TokenKind::Identifier if t.literal == "Method" && self.token_at(self.currently_at + 1).kind == TokenKind::OpeningBrace => {
+                self.currently_at += 1;
+                let params = self.parse_parameters()?;
+                let mut return_type = None;
+                if self.current_token().kind == TokenKind::As {
+                    self.currently_at += 1;
+                    return_type = Some(Box::new(self.parse_single_type(in_expression)?));
+                }
+                MonkeyCTypeExpression::Callback { params, return_type, span: self.span_from(t.span) }
+            }
+            TokenKind::Identifier => {
+                let name = self.parse_qualified_name()?;
+                let mut generics = Vec::new();
+                if self.current_token().kind == TokenKind::LessThan {
+                    self.currently_at += 1;
+                    loop {
+                        generics.push(self.parse_type()?);
+                        if self.current_token().kind != TokenKind::Comma {
+                            break;
+                        }
+                        self.currently_at += 1;
+                    }
+                    self.expect_closing_angle()?;
+                }
+                MonkeyCTypeExpression::Named { name, generics, span: self.span_from(t.span) }
+            }
+            TokenKind::OpeningBracket => {
+                self.currently_at += 1;
+                let mut entries = Vec::new();
+                while self.current_token().kind != TokenKind::ClosingBracket {
+                    let key = self.current_token();
+                    let kind = match key.kind {
+                        TokenKind::SymbolLiteral => MonkeyCLiteralKind::Symbol,
+                        TokenKind::StringLiteral => MonkeyCLiteralKind::String,
+                        _ => {
+                            self.error_expected("a symbol or a string");
+                            return None;
+                        }
+                    };
+                    self.currently_at += 1;
+                    self.expect(TokenKind::As, "'as'")?;
+                    let value = self.parse_type()?;
+                    entries.push((MonkeyCExpression::Simple { value: key.literal, kind, span: key.span }, value));
+                    if self.current_token().kind == TokenKind::Comma {
+                        self.currently_at += 1;
+                    } else if self.current_token().kind != TokenKind::ClosingBracket {
+                        self.error_expected("',' or '}'");
+                        return None;
+                    }
+                }
+                self.currently_at += 1;
+                MonkeyCTypeExpression::DictionaryShape { entries, span: self.span_from(t.span) }
+            }
+            TokenKind::OpeningSquareBracket => {
+                self.currently_at += 1;
+                let mut types = Vec::new();
+                while self.current_token().kind != TokenKind::ClosingSquareBracket {
+                    types.push(self.parse_type()?);
+                    if self.current_token().kind == TokenKind::Comma {
+                        self.currently_at += 1;
+                    } else if self.current_token().kind != TokenKind::ClosingSquareBracket {
+                        self.error_expected("',' or ']'");
+                        return None;
+                    }
+                }
+                self.currently_at += 1;
+                MonkeyCTypeExpression::Tuple { types, span: self.span_from(t.span) }
+            }
+            _ => {
+                self.error_expected("a type");
+                return None;
+            }
+        };
+        while self.current_token().kind == TokenKind::QuestionMark &&
+            !(in_expression && starts_expression(&self.token_at(self.currently_at + 1).kind)) {
+            self.deeper("Type")?;
+            self.currently_at += 1;
+            single = MonkeyCTypeExpression::Nullable { inner: Box::new(single), span: self.span_from(t.span) };
+        }
+        Some(single)
+    }
+
+    /// Consumes the `>` that closes a list of generics. The
+    /// lexer turns `>>`, `>=` and `>>=` into single tokens, so
+    /// those get split in two, and the first half is consumed.
+    fn expect_closing_angle(&mut self) -> Option<Token> {
+        let t = self.current_token();
+        let rest = match t.kind {
+            TokenKind::GreaterThan => {
+                self.currently_at += 1;
+                return Some(t);
+            }
+            TokenKind::RightShift => TokenKind::GreaterThan,
+            TokenKind::GreaterThanEquals => TokenKind::Assign,
+            TokenKind::RightShiftAssign => TokenKind::GreaterThanEquals,
+            _ => {
+                self.error_expected("'>'");
+                return None;
+            }
+        };
+        let mut first = Token::new(TokenKind::GreaterThan, ">".to_string(), t.row, t.column);
+        first.span = Span::new(t.span.file_id, t.span.start, t.span.start + 1);
+        first.leading_trivia = t.leading_trivia.clone();
+        let mut second = Token::new(rest, t.literal[1..].to_string(), t.row, t.column + 1);
+        second.span = Span::new(t.span.file_id, t.span.start + 1, t.span.end);
+        second.trailing_trivia = t.trailing_trivia;
+
+        self.token_list[self.currently_at] = second;
+        self.token_list.insert(self.currently_at, first.clone());
+        self.currently_at += 1;
+        Some(first)
+    }
+}
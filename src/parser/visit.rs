@@ -0,0 +1,324 @@
+//! Traversal of the syntax tree.
+//!
+//! Every `visit_*` method of [`Visitor`] and [`VisitorMut`] calls the
+//! matching `walk_*` function by default, which visits the children
+//! of the node. An implementation overrides only the nodes it's
+//! interested in, and calls `walk_*` itself to keep going deeper.
+
+use crate::parser::ast::{MonkeyCAnnotation, MonkeyCCatch, MonkeyCEnumMember, MonkeyCExpression, MonkeyCParameter,
+                         MonkeyCStatement, MonkeyCSwitchCase, MonkeyCTypeExpression, MonkeyCVariableDeclarator};
+
+/// Both traits only differ in mutability, so they
+/// are generated from the same definition
+macro_rules! make_visitor {
+    ($(#[$attr:meta])* $visitor:ident, $($mutability:ident)?;
+     $visit_statement:ident, $walk_statement:ident;
+     $visit_expression:ident, $walk_expression:ident;
+     $visit_type:ident, $walk_type:ident;
+     $visit_annotation:ident, $walk_annotation:ident;
+     $visit_variable_declarator:ident, $walk_variable_declarator:ident;
+     $visit_parameter:ident, $walk_parameter:ident;
+     $visit_enum_member:ident, $walk_enum_member:ident;
+     $visit_switch_case:ident, $walk_switch_case:ident;
+     $visit_catch:ident, $walk_catch:ident) => {
+        $(#[$attr])*
+        pub trait $visitor {
+            fn $visit_statement(&mut self, statement: &$($mutability)? MonkeyCStatement) {
+                $walk_statement(self, statement)
+            }
+
+            fn $visit_expression(&mut self, expression: &$($mutability)? MonkeyCExpression) {
+                $walk_expression(self, expression)
+            }
+
+            fn $visit_type(&mut self, type_expression: &$($mutability)? MonkeyCTypeExpression) {
+                $walk_type(self, type_expression)
+            }
+
+            fn $visit_annotation(&mut self, annotation: &$($mutability)? MonkeyCAnnotation) {
+                $walk_annotation(self, annotation)
+            }
+
+            fn $visit_variable_declarator(&mut self, declarator: &$($mutability)? MonkeyCVariableDeclarator) {
+                $walk_variable_declarator(self, declarator)
+            }
+
+            fn $visit_parameter(&mut self, parameter: &$($mutability)? MonkeyCParameter) {
+                $walk_parameter(self, parameter)
+            }
+
+            fn $visit_enum_member(&mut self, member: &$($mutability)? MonkeyCEnumMember) {
+                $walk_enum_member(self, member)
+            }
+
+            fn $visit_switch_case(&mut self, case: &$($mutability)? MonkeyCSwitchCase) {
+                $walk_switch_case(self, case)
+            }
+
+            fn $visit_catch(&mut self, catch: &$($mutability)? MonkeyCCatch) {
+                $walk_catch(self, catch)
+            }
+        }
+
+        pub fn $walk_statement<V: $visitor + ?Sized>(visitor: &mut V, statement: &$($mutability)? MonkeyCStatement) {
+            match statement {
+                MonkeyCStatement::VariableDeclaration { declarators, annotations, .. } => {
+                    for annotation in annotations {
+                        visitor.$visit_annotation(annotation);
+                    }
+                    for declarator in declarators {
+                        visitor.$visit_variable_declarator(declarator);
+                    }
+                }
+                MonkeyCStatement::ClassDeclaration { annotations, children, .. } |
+                MonkeyCStatement::ModuleDeclaration { annotations, children, .. } => {
+                    for annotation in annotations {
+                        visitor.$visit_annotation(annotation);
+                    }
+                    for child in children {
+                        visitor.$visit_statement(child);
+                    }
+                }
+                MonkeyCStatement::FunctionDeclaration { params, return_type, annotations, body, .. } => {
+                    for annotation in annotations {
+                        visitor.$visit_annotation(annotation);
+                    }
+                    for param in params {
+                        visitor.$visit_parameter(param);
+                    }
+                    if let Some(return_type) = return_type {
+                        visitor.$visit_type(return_type);
+                    }
+                    visitor.$visit_statement(body);
+                }
+                MonkeyCStatement::EnumDeclaration { members, annotations, .. } => {
+                    for annotation in annotations {
+                        visitor.$visit_annotation(annotation);
+                    }
+                    for member in members {
+                        visitor.$visit_enum_member(member);
+                    }
+                }
+                MonkeyCStatement::ExpressionStatement { expression, .. } => visitor.$visit_expression(expression),
+                MonkeyCStatement::Block { children, .. } => {
+                    for child in children {
+                        visitor.$visit_statement(child);
+                    }
+                }
+                MonkeyCStatement::If { condition, then_branch, else_branch, .. } => {
+                    visitor.$visit_expression(condition);
+                    visitor.$visit_statement(then_branch);
+                    if let Some(else_branch) = else_branch {
+                        visitor.$visit_statement(else_branch);
+                    }
+                }
+                MonkeyCStatement::While { condition, body, .. } => {
+                    visitor.$visit_expression(condition);
+                    visitor.$visit_statement(body);
+                }
+                MonkeyCStatement::DoWhile { body, condition, .. } => {
+                    visitor.$visit_statement(body);
+                    visitor.$visit_expression(condition);
+                }
+                MonkeyCStatement::For { init, condition, update, body, .. } => {
+                    if let Some(init) = init {
+                        visitor.$visit_statement(init);
+                    }
+                    if let Some(condition) = condition {
+                        visitor.$visit_expression(condition);
+                    }
+                    if let Some(update) = update {
+                        visitor.$visit_expression(update);
+                    }
+                    visitor.$visit_statement(body);
+                }
+                MonkeyCStatement::Switch { value, cases, .. } => {
+                    visitor.$visit_expression(value);
+                    for case in cases {
+                        visitor.$visit_switch_case(case);
+                    }
+                }
+                MonkeyCStatement::Return { value, .. } => {
+                    if let Some(value) = value {
+                        visitor.$visit_expression(value);
+                    }
+                }
+                MonkeyCStatement::Throw { value, .. } => visitor.$visit_expression(value),
+                MonkeyCStatement::Try { body, catches, finally, .. } => {
+                    visitor.$visit_statement(body);
+                    for catch in catches {
+                        visitor.$visit_catch(catch);
+                    }
+                    if let Some(finally) = finally {
+                        visitor.$visit_statement(finally);
+                    }
+                }
+                MonkeyCStatement::Using { annotations, .. } | MonkeyCStatement::Import { annotations, .. } => {
+                    for annotation in annotations {
+                        visitor.$visit_annotation(annotation);
+                    }
+                }
+                MonkeyCStatement::Empty { .. } | MonkeyCStatement::Error { .. } | MonkeyCStatement::Break { .. } | MonkeyCStatement::Continue { .. } => {}
+            }
+        }
+
+        pub fn $walk_expression<V: $visitor + ?Sized>(visitor: &mut V, expression: &$($mutability)? MonkeyCExpression) {
+            match expression {
+                MonkeyCExpression::Mathematical { lhs, rhs, .. } |
+                MonkeyCExpression::Binary { lhs, rhs, .. } |
+                MonkeyCExpression::Bitwise { lhs, rhs, .. } |
+                MonkeyCExpression::Logical { lhs, rhs, .. } |
+                MonkeyCExpression::Elvis { lhs, rhs, .. } => {
+                    visitor.$visit_expression(lhs);
+                    visitor.$visit_expression(rhs);
+                }
+                MonkeyCExpression::Unary { operand, .. } | MonkeyCExpression::Update { operand, .. } =>
+                    visitor.$visit_expression(operand),
+                MonkeyCExpression::Ternary { condition, if_true, if_false, .. } => {
+                    visitor.$visit_expression(condition);
+                    visitor.$visit_expression(if_true);
+                    visitor.$visit_expression(if_false);
+                }
+                MonkeyCExpression::Assignment { target, value, .. } => {
+                    visitor.$visit_expression(target);
+                    visitor.$visit_expression(value);
+                }
+                MonkeyCExpression::Cast { value, target_type, .. } => {
+                    visitor.$visit_expression(value);
+                    visitor.$visit_type(target_type);
+                }
+                MonkeyCExpression::InstanceOf { value, class, .. } => {
+                    visitor.$visit_expression(value);
+                    visitor.$visit_expression(class);
+                }
+                MonkeyCExpression::Has { value, member, .. } => {
+                    visitor.$visit_expression(value);
+                    visitor.$visit_expression(member);
+                }
+                MonkeyCExpression::Member { object, .. } => visitor.$visit_expression(object),
+                MonkeyCExpression::Index { object, index, .. } => {
+                    visitor.$visit_expression(object);
+                    visitor.$visit_expression(index);
+                }
+                MonkeyCExpression::Call { callee: function, args, .. } |
+                MonkeyCExpression::New { class: function, args, .. } => {
+                    visitor.$visit_expression(function);
+                    for arg in args {
+                        visitor.$visit_expression(arg);
+                    }
+                }
+                MonkeyCExpression::NewArray { size, .. } => visitor.$visit_expression(size),
+                MonkeyCExpression::Array { elements, .. } => {
+                    for element in elements {
+                        visitor.$visit_expression(element);
+                    }
+                }
+                MonkeyCExpression::Dictionary { entries, .. } => {
+                    for (key, value) in entries {
+                        visitor.$visit_expression(key);
+                        visitor.$visit_expression(value);
+                    }
+                }
+                MonkeyCExpression::Simple { .. } | MonkeyCExpression::Reference { .. } => {}
+            }
+        }
+
+        pub fn $walk_type<V: $visitor + ?Sized>(visitor: &mut V, type_expression: &$($mutability)? MonkeyCTypeExpression) {
+            match type_expression {
+                MonkeyCTypeExpression::Named { generics: types, .. } |
+                MonkeyCTypeExpression::Union { types, .. } |
+                MonkeyCTypeExpression::Tuple { types, .. } => {
+                    for inner in types {
+                        visitor.$visit_type(inner);
+                    }
+                }
+                MonkeyCTypeExpression::Nullable { inner, .. } => visitor.$visit_type(inner),
+                MonkeyCTypeExpression::Callback { params, return_type, .. } => {
+                    for param in params {
+                        visitor.$visit_parameter(param);
+                    }
+                    if let Some(return_type) = return_type {
+                        visitor.$visit_type(return_type);
+                    }
+                }
+                MonkeyCTypeExpression::DictionaryShape { entries, .. } => {
+                    for (key, value_type) in entries {
+                        visitor.$visit_expression(key);
+                        visitor.$visit_type(value_type);
+                    }
+                }
+            }
+        }
+
+        pub fn $walk_annotation<V: $visitor + ?Sized>(visitor: &mut V, annotation: &$($mutability)? MonkeyCAnnotation) {
+            for arg in &$($mutability)? annotation.args {
+                visitor.$visit_expression(arg);
+            }
+        }
+
+        pub fn $walk_variable_declarator<V: $visitor + ?Sized>(visitor: &mut V,
+                                                              declarator: &$($mutability)? MonkeyCVariableDeclarator) {
+            if let Some(var_type) = &$($mutability)? declarator.var_type {
+                visitor.$visit_type(var_type);
+            }
+            if let Some(default_val) = &$($mutability)? declarator.default_val {
+                visitor.$visit_expression(default_val);
+            }
+        }
+
+        pub fn $walk_parameter<V: $visitor + ?Sized>(visitor: &mut V, parameter: &$($mutability)? MonkeyCParameter) {
+            if let Some(param_type) = &$($mutability)? parameter.param_type {
+                visitor.$visit_type(param_type);
+            }
+        }
+
+        pub fn $walk_enum_member<V: $visitor + ?Sized>(visitor: &mut V, member: &$($mutability)? MonkeyCEnumMember) {
+            if let Some(value) = &$($mutability)? member.value {
+                visitor.$visit_expression(value);
+            }
+        }
+
+        pub fn $walk_switch_case<V: $visitor + ?Sized>(visitor: &mut V, case: &$($mutability)? MonkeyCSwitchCase) {
+            if let Some(value) = &$($mutability)? case.value {
+                visitor.$visit_expression(value);
+            }
+            for child in &$($mutability)? case.children {
+                visitor.$visit_statement(child);
+            }
+        }
+
+        pub fn $walk_catch<V: $visitor + ?Sized>(visitor: &mut V, catch: &$($mutability)? MonkeyCCatch) {
+            visitor.$visit_statement(&$($mutability)? catch.body);
+        }
+    }
+}
+
+make_visitor!(
+    /// Walks the tree by shared reference, for
+    /// rules that only look at the code
+    Visitor,;
+    visit_statement, walk_statement;
+    visit_expression, walk_expression;
+    visit_type, walk_type;
+    visit_annotation, walk_annotation;
+    visit_variable_declarator, walk_variable_declarator;
+    visit_parameter, walk_parameter;
+    visit_enum_member, walk_enum_member;
+    visit_switch_case, walk_switch_case;
+    visit_catch, walk_catch
+);
+
+make_visitor!(
+    /// Walks the tree by mutable reference, for
+    /// code that rewrites it in place
+    VisitorMut, mut;
+    visit_statement_mut, walk_statement_mut;
+    visit_expression_mut, walk_expression_mut;
+    visit_type_mut, walk_type_mut;
+    visit_annotation_mut, walk_annotation_mut;
+    visit_variable_declarator_mut, walk_variable_declarator_mut;
+    visit_parameter_mut, walk_parameter_mut;
+    visit_enum_member_mut, walk_enum_member_mut;
+    visit_switch_case_mut, walk_switch_case_mut;
+    visit_catch_mut, walk_catch_mut
+);
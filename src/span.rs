@@ -0,0 +1,100 @@
+use serde::Serialize;
+
+/// Identifies a source file a span belongs to.
+/// It's up to the caller to map ids to actual files.
+pub type FileId = u32;
+
+/// A range of bytes inside of a source file.
+/// `start` is inclusive, `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+pub struct Span {
+    pub file_id: FileId,
+    pub start: usize,
+    pub end: usize
+}
+
+impl Span {
+    pub fn new(file_id: FileId, start: usize, end: usize) -> Self {
+        Self { file_id, start, end }
+    }
+
+    /// Returns a span that covers both `self` and `other`
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.file_id, self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+/// Maps byte offsets of a source file back to lines and columns.
+///
+/// All lines and columns are 1-based. Columns are counted in
+/// characters by `line_col` and in UTF-16 code units by
+/// `line_col_utf16`, which is what most editors expect.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    source: String,
+    /// Byte offset of the first character of every line
+    line_starts: Vec<usize>
+}
+
+impl LineIndex {
+    /// Lines are broken like the lexer does it,
+    /// by `\n`, `\r\n` and a lone `\r`
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        let bytes = source.as_bytes();
+        for (offset, byte) in bytes.iter().enumerate() {
+            let breaks = match byte {
+                b'\n' => true,
+                b'\r' => bytes.get(offset + 1) != Some(&b'\n'),
+                _ => false,
+            };
+            if breaks {
+                line_starts.push(offset + 1);
+            }
+        }
+        Self { source: source.to_string(), line_starts }
+    }
+
+    /// Returns the line `offset` is on. Offsets past
+    /// the end of the source are clamped to it.
+    pub fn line(&self, offset: usize) -> u64 {
+        let offset = offset.min(self.source.len());
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line as u64 + 1,
+            Err(line) => line as u64,
+        }
+    }
+
+    /// Returns the line and column (in characters) of `offset`
+    pub fn line_col(&self, offset: usize) -> (u64, u64) {
+        let line = self.line(offset);
+        let prefix = self.line_prefix(line, offset);
+        (line, prefix.chars().count() as u64 + 1)
+    }
+
+    /// Returns the line and column (in UTF-16 code units) of `offset`
+    pub fn line_col_utf16(&self, offset: usize) -> (u64, u64) {
+        let line = self.line(offset);
+        let prefix = self.line_prefix(line, offset);
+        (line, prefix.encode_utf16().count() as u64 + 1)
+    }
+
+    /// Returns the text of a line, without the line break
+    pub fn line_text(&self, line: u64) -> &str {
+        let index = (line as usize).saturating_sub(1).min(self.line_starts.len() - 1);
+        let start = self.line_starts[index];
+        let end = self.line_starts.get(index + 1).copied().unwrap_or(self.source.len());
+        self.source[start..end].trim_end_matches('\n').trim_end_matches('\r')
+    }
+
+    /// Text between the start of `line` and `offset`. If `offset`
+    /// falls inside of a character, that character is excluded.
+    fn line_prefix(&self, line: u64, offset: usize) -> &str {
+        let start = self.line_starts[line as usize - 1];
+        let mut end = offset.min(self.source.len());
+        while !self.source.is_char_boundary(end) {
+            end -= 1;
+        }
+        &self.source[start..end]
+    }
+}
@@ -0,0 +1,31 @@
+use crate::lexer::tokens::TokenKind;
+use crate::span::Span;
+use crate::{lex, lint, parse, Config, Diagnostic, DiagnosticKind};
+use std::path::Path;
+
+#[test]
+fn lex_and_parse_report_diagnostics() {
+    let (tokens, diagnostics) = lex("var x = 1;");
+    assert!(diagnostics.is_empty());
+    assert_eq!(tokens.last().map(|t| &t.kind), Some(&TokenKind::Eof));
+
+    let (statements, diagnostics) = parse("var x = 1;\nvar y = ;");
+    assert_eq!(statements.len(), 2);
+    assert_eq!(diagnostics, vec![Diagnostic {
+        kind: DiagnosticKind::SyntaxError,
+        at: (2, 9),
+        span: Span::new(0, 19, 20),
+        message: "Expected an expression, found ';'".to_string()
+    }]);
+}
+
+#[test]
+fn lint_reports_lexing_errors_first() {
+    let diagnostics = lint("var x = #;\nvar y = ;", &Config::default());
+    let kinds: Vec<DiagnosticKind> = diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(kinds[0], DiagnosticKind::BadToken);
+    assert_eq!(kinds.last(), Some(&DiagnosticKind::SyntaxError));
+    assert_eq!(diagnostics[0].full_message(Path::new("a.mc")), "Bad token at 1:9: Unknown character '#'");
+    assert_eq!(diagnostics.last().unwrap().full_message(Path::new("a.mc")),
+               "Syntax error at a.mc:2:9: Expected an expression, found ';'");
+}
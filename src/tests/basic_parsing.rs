@@ -1,21 +1,24 @@
 use crate::lexer::MonkeyCLexer;
 use crate::parser::MonkeyCParser;
-use anyhow::Context;
 use anyhow::Result;
 use crate::parser::ast::MonkeyCStatement::VariableDeclaration;
-use crate::parser::ast::MonkeyCExpression;
+use crate::parser::ast::{MonkeyCExpression, MonkeyCLiteralKind, MonkeyCTypeExpression, MonkeyCVariableDeclarator};
+use crate::span::Span;
 use std::path::PathBuf;
 
 #[test]
 fn basic_code() -> Result<()> {
     let data = "var myVar as String = \"Hm\";\nvar myOtherVar = myVar;";
     let mut lexer = MonkeyCLexer::new(data.chars().collect());
-    let tokens = lexer.lex().with_context(|| "Failed to tokenize data")?;
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty());
     println!("{:?}", tokens.clone());
     let mut parser = MonkeyCParser::new(tokens, PathBuf::from("<test>"));
 
     // Check for equality of parsed syntax
-    assert_eq!(parser.parse().unwrap(), vec![VariableDeclaration { name: "myVar".to_string(), default_val: MonkeyCExpression::Simple("Hm".to_string()), var_type: Some("String".to_string()), is_const: false },
-                                                                             VariableDeclaration { name: "myOtherVar".to_string(), default_val: MonkeyCExpression::Reference("myVar".to_string()), var_type: None, is_const: false }]);
+    let (statements, errors) = parser.parse();
+    assert!(errors.is_empty());
+    assert_eq!(statements, vec![VariableDeclaration { declarators: vec![MonkeyCVariableDeclarator { name: "myVar".to_string(), default_val: Some(MonkeyCExpression::Simple { value: "Hm".to_string(), kind: MonkeyCLiteralKind::String, span: Span::new(0, 22, 26) }), var_type: Some(MonkeyCTypeExpression::Named { name: "String".to_string(), generics: vec![], span: Span::new(0, 13, 19) }), span: Span::new(0, 4, 26) }], is_const: false, annotations: vec![], modifiers: vec![], span: Span::new(0, 0, 27) },
+                                                                             VariableDeclaration { declarators: vec![MonkeyCVariableDeclarator { name: "myOtherVar".to_string(), default_val: Some(MonkeyCExpression::Reference { name: "myVar".to_string(), span: Span::new(0, 45, 50) }), var_type: None, span: Span::new(0, 32, 50) }], is_const: false, annotations: vec![], modifiers: vec![], span: Span::new(0, 28, 51) }]);
     Ok(())
 }
@@ -0,0 +1,88 @@
+use crate::cst::green::NodeKind;
+use crate::cst::red::{SyntaxElement, SyntaxNode};
+use crate::cst;
+use crate::lexer::MonkeyCLexer;
+use crate::lexer::tokens::TokenKind;
+use proptest::prelude::*;
+
+fn build(source: &str) -> SyntaxNode {
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, _) = lexer.lex();
+    cst::build(source, &tokens)
+}
+
+#[test]
+fn round_trips_source() {
+    let cases = ["", "  \n", "var x = 1;", "// Doc\r\nclass A {\n\tvar b = [1, 2];\t/* c */\n}\r\n",
+                 "function f(a, b) { return (a + b) * 2; } // done", "var s = \"unterminated", "/* open", "}{)(][",
+                 "var é = \"日本\"; :sym\r", "0x1Fl 1e5 '\\u00e9' #"];
+    for case in cases.iter() {
+        assert_eq!(build(case).to_string(), *case);
+    }
+}
+
+#[test]
+fn groups_tokens_by_brackets() {
+    let root = build("f(a[1]) { }");
+    assert_eq!(root.kind(), NodeKind::SourceFile);
+
+    let children = root.children();
+    assert_eq!(children.len(), 4);
+    let call = match &children[1] {
+        SyntaxElement::Node(node) => node.clone(),
+        other => panic!("expected a node, got {:?}", other),
+    };
+    assert_eq!(call.kind(), NodeKind::BraceGroup);
+    assert_eq!(call.full_range(), 1..8);
+    assert_eq!(call.parent().map(|p| p.kind()), Some(NodeKind::SourceFile));
+    assert!(matches!(&call.children()[2], SyntaxElement::Node(n) if n.kind() == NodeKind::SquareBracketGroup));
+
+    let block = match &children[2] {
+        SyntaxElement::Node(node) => node.clone(),
+        other => panic!("expected a node, got {:?}", other),
+    };
+    assert_eq!(block.kind(), NodeKind::BracketGroup);
+    assert_eq!(block.full_range(), 8..11);
+    assert!(matches!(&children[3], SyntaxElement::Token(t) if *t.kind() == TokenKind::Eof));
+}
+
+#[test]
+fn tokens_know_their_offsets() {
+    let source = "var x = 1; // one\n  x++;";
+    let root = build(source);
+    for token in root.tokens() {
+        assert_eq!(&source[token.text_range()], token.text());
+    }
+    let tokens = root.tokens();
+    assert_eq!(tokens[4].text(), ";");
+    assert_eq!(tokens[4].full_range(), 9..18);
+    assert_eq!(tokens[5].full_range(), 18..21);
+    assert_eq!(tokens[5].text_range(), 20..21);
+}
+
+#[test]
+fn unbalanced_brackets_are_kept() {
+    let root = build("{ ( }");
+    let block = match &root.children()[0] {
+        SyntaxElement::Node(node) => node.clone(),
+        other => panic!("expected a node, got {:?}", other),
+    };
+    // The stray `}` doesn't close the brace group, so both stay open until the end
+    assert_eq!(block.kind(), NodeKind::BracketGroup);
+    assert_eq!(block.full_range(), 0..5);
+    assert_eq!(root.children().len(), 2);
+}
+
+proptest! {
+    #[test]
+    fn printing_the_tree_gives_back_the_input(source in "\\PC*") {
+        prop_assert_eq!(build(&source).to_string(), source);
+    }
+
+    #[test]
+    fn printing_code_like_trees_gives_back_the_input(
+        source in "[ \t\r\n{}()\\[\\]a-z0-9;:=+\\-*/\"'.,?<>!&|^%#@$\\\\]{0,64}"
+    ) {
+        prop_assert_eq!(build(&source).to_string(), source);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use crate::parser::ast::{MonkeyCAnnotation, MonkeyCExpression, MonkeyCModifier, MonkeyCParameter, MonkeyCStatement, MonkeyCTypeExpression};
+use crate::span::Span;
+use crate::parser::err::MCParseError;
+use std::path::PathBuf;
+
+fn parse_with_errors(source: &str) -> (Vec<MonkeyCStatement>, Vec<MCParseError>) {
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+    MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse()
+}
+
+fn parse(source: &str) -> Vec<MonkeyCStatement> {
+    let (statements, errors) = parse_with_errors(source);
+    assert!(errors.is_empty(), "{:?}", errors);
+    statements
+}
+
+fn named(name: &str, start: usize, end: usize) -> MonkeyCTypeExpression {
+    MonkeyCTypeExpression::Named { name: name.to_string(), generics: vec![], span: Span::new(0, start, end) }
+}
+
+fn annotation_names(annotations: &[MonkeyCAnnotation]) -> Vec<&str> {
+    annotations.iter().map(|a| a.name.as_str()).collect()
+}
+
+#[test]
+fn class_with_members() {
+    let source = "(:background)\nclass Foo extends Toybox.Application.AppBase {\n    private var bar = 1;\n    \
+                  static const BAZ = \"baz\";\n    hidden class Inner { }\n}";
+    let statements = parse(source);
+    assert_eq!(statements.len(), 1);
+    match &statements[0] {
+        MonkeyCStatement::ClassDeclaration { name, extends, annotations, modifiers, children, span } => {
+            assert_eq!(name, "Foo");
+            assert_eq!(extends.as_deref(), Some("Toybox.Application.AppBase"));
+            assert_eq!(annotation_names(annotations), vec!["background"]);
+            assert!(modifiers.is_empty());
+            assert_eq!((span.start, span.end), (0, source.len()));
+
+            assert_eq!(children.len(), 3);
+            match &children[0] {
+                MonkeyCStatement::VariableDeclaration { declarators, is_const, modifiers, .. } => {
+                    assert_eq!(declarators[0].name, "bar");
+                    assert!(!is_const);
+                    assert_eq!(modifiers, &vec![MonkeyCModifier::Private]);
+                }
+                other => panic!("expected a variable, got {:?}", other),
+            }
+            match &children[1] {
+                MonkeyCStatement::VariableDeclaration { declarators, is_const, modifiers, .. } => {
+                    assert_eq!(declarators[0].name, "BAZ");
+                    assert!(is_const);
+                    assert_eq!(modifiers, &vec![MonkeyCModifier::Static]);
+                }
+                other => panic!("expected a constant, got {:?}", other),
+            }
+            match &children[2] {
+                MonkeyCStatement::ClassDeclaration { name, modifiers, children, .. } => {
+                    assert_eq!(name, "Inner");
+                    assert_eq!(modifiers, &vec![MonkeyCModifier::Hidden]);
+                    assert!(children.is_empty());
+                }
+                other => panic!("expected a class, got {:?}", other),
+            }
+        }
+        other => panic!("expected a class, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_classes_are_reported() {
+    let cases = [("class { }", "Expected a class name, found '{'"),
+                 ("class Foo extends { }", "Expected an identifier, found '{'"),
+                 ("class Foo { 5; }", "Expected a class member, found '5'"),
+                 ("class Foo { var x = 1;", "Expected '}', found end of file"),
+                 ("(:test class Foo {}", "Expected ')', found 'class'"),
+                 ("(:test, ) class Foo {}", "Expected an annotation, found ')'"),
+                 ("(:typecheck(false) class Foo {}", "Expected ')', found 'class'")];
+    for (source, message) in cases.iter() {
+        let (_, errors) = parse_with_errors(source);
+        assert_eq!(errors[0].msg, *message, "{}", source);
+    }
+}
+
+#[test]
+fn variables_with_several_declarators() {
+    let source = "var a;\nvar b as Number, c = foo(1) + 2;\nconst D = 1, E as String = \"e\";";
+    let statements = parse(source);
+    assert_eq!(statements.len(), 3);
+    let declarators = |statement: &MonkeyCStatement| match statement {
+        MonkeyCStatement::VariableDeclaration { declarators, is_const, .. } => declarators.iter()
+            .map(|d| (d.name.clone(), d.var_type.is_some(), d.default_val.is_some(), *is_const)).collect::<Vec<_>>(),
+        other => panic!("expected a variable, got {:?}", other),
+    };
+    assert_eq!(declarators(&statements[0]), vec![("a".to_string(), false, false, false)]);
+    assert_eq!(declarators(&statements[1]), vec![("b".to_string(), true, false, false), ("c".to_string(), false, true, false)]);
+    assert_eq!(declarators(&statements[2]), vec![("D".to_string(), false, true, true), ("E".to_string(), true, true, true)]);
+
+    match &statements[1] {
+        MonkeyCStatement::VariableDeclaration { declarators, span, .. } => {
+            assert_eq!((span.start, span.end), (7, 39));
+            assert_eq!(declarators[0].var_type, Some(named("Number", 16, 22)));
+            assert_eq!((declarators[1].span.start, declarators[1].span.end), (24, 38));
+        }
+        other => panic!("expected a variable, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_variables_are_reported() {
+    let cases = [("var;", "Expected an identifier, found ';'"),
+                 ("var a, ;", "Expected an identifier, found ';'"),
+                 ("var a = ;", "Expected an expression, found ';'"),
+                 ("var a b;", "Expected ';', found 'b'"),
+                 ("const A;", "Expected '=', found ';'"),
+                 ("const A as Number, B = 1;", "Expected '=', found ','")];
+    for (source, message) in cases.iter() {
+        let (_, errors) = parse_with_errors(source);
+        assert_eq!(errors[0].msg, *message, "{}", source);
+    }
+}
+
+#[test]
+fn annotations_with_arguments() {
+    let source = "(:typecheck(false))\nfunction f() {}\n(:debug, :test) (:release)\nhidden var x;\n(:background) const A = 1;\n\
+                  (:debug) enum { A }";
+    let statements = parse(source);
+    assert_eq!(statements.len(), 4);
+    match &statements[0] {
+        MonkeyCStatement::FunctionDeclaration { annotations, .. } => {
+            assert_eq!(annotations.len(), 1);
+            assert_eq!(annotations[0].name, "typecheck");
+            assert!(matches!(&annotations[0].args[..], [MonkeyCExpression::Simple { value, .. }] if value == "false"));
+            assert_eq!(&source[annotations[0].span.start..annotations[0].span.end], ":typecheck(false)");
+        }
+        other => panic!("expected a function, got {:?}", other),
+    }
+    match &statements[1] {
+        MonkeyCStatement::VariableDeclaration { annotations, modifiers, span, .. } => {
+            assert_eq!(annotation_names(annotations), vec!["debug", "test", "release"]);
+            assert!(annotations.iter().all(|a| a.args.is_empty()));
+            assert_eq!(modifiers, &vec![MonkeyCModifier::Hidden]);
+            assert_eq!(&source[span.start..span.end], "(:debug, :test) (:release)\nhidden var x;");
+        }
+        other => panic!("expected a variable, got {:?}", other),
+    }
+    assert!(matches!(&statements[2], MonkeyCStatement::VariableDeclaration { annotations, is_const: true, .. }
+        if annotation_names(annotations) == vec!["background"]));
+    assert!(matches!(&statements[3], MonkeyCStatement::EnumDeclaration { annotations, .. }
+        if annotation_names(annotations) == vec!["debug"]));
+}
+
+#[test]
+fn functions_with_parameters_and_return_types() {
+    let source = "class A {\n    (:test)\n    hidden static function add(a as Number, b as Lang.Number or Null, c) as Number {\n        \
+                  var sum = a + b;\n        {\n            var inner = sum;\n        }\n        return sum;\n    }\n}";
+    let statements = parse(source);
+    let function = match &statements[0] {
+        MonkeyCStatement::ClassDeclaration { children, .. } => &children[0],
+        other => panic!("expected a class, got {:?}", other),
+    };
+    match function {
+        MonkeyCStatement::FunctionDeclaration { name, params, return_type, annotations, modifiers, body, span } => {
+            assert_eq!(name, "add");
+            assert_eq!(params, &vec![
+                MonkeyCParameter { name: "a".to_string(), param_type: Some(named("Number", 58, 64)), span: Span::new(0, 53, 64) },
+                MonkeyCParameter {
+                    name: "b".to_string(),
+                    param_type: Some(MonkeyCTypeExpression::Union {
+                        types: vec![named("Lang.Number", 71, 82), named("Null", 86, 90)],
+                        span: Span::new(0, 71, 90)
+                    }),
+                    span: Span::new(0, 66, 90)
+                },
+                MonkeyCParameter { name: "c".to_string(), param_type: None, span: Span::new(0, 92, 93) },
+            ]);
+            assert!(matches!(return_type, Some(MonkeyCTypeExpression::Named { name, .. }) if name == "Number"));
+            assert_eq!(annotation_names(annotations), vec!["test"]);
+            assert_eq!(modifiers, &vec![MonkeyCModifier::Hidden, MonkeyCModifier::Static]);
+            assert_eq!(&source[span.start..span.end], &source[14..source.len() - 2]);
+
+            match body.as_ref() {
+                MonkeyCStatement::Block { children, .. } => {
+                    assert_eq!(children.len(), 3);
+                    assert!(matches!(&children[0], MonkeyCStatement::VariableDeclaration { declarators, .. } if declarators[0].name == "sum"));
+                    assert!(matches!(&children[1], MonkeyCStatement::Block { children, .. } if children.len() == 1));
+                    assert!(matches!(&children[2], MonkeyCStatement::Return { value: Some(_), .. }));
+                }
+                other => panic!("expected a block, got {:?}", other),
+            }
+        }
+        other => panic!("expected a function, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_functions_are_reported() {
+    let cases = [("function () {}", "Expected a function name, found '('"),
+                 ("function f(a b) {}", "Expected ',' or ')', found 'b'"),
+                 ("function f(a as) {}", "Expected a type, found ')'"),
+                 ("function f() as Void;", "Expected '{', found ';'"),
+                 ("function f() { var x = 1;", "Expected '}', found end of file")];
+    for (source, message) in cases.iter() {
+        let (_, errors) = parse_with_errors(source);
+        assert_eq!(errors[0].msg, *message, "{}", source);
+    }
+}
+
+#[test]
+fn modules_imports_and_enums() {
+    let source = "import Toybox.Lang;\nusing Toybox.Graphics as Gfx;\nusing Toybox.System;\n(:glance)\nmodule Utils {\n    \
+                  enum { A = 1, B, C = A << 2, }\n    module Inner { }\n}\nhidden enum Direction { UP, DOWN };";
+    let statements = parse(source);
+    assert_eq!(statements.len(), 5);
+    assert!(matches!(&statements[0], MonkeyCStatement::Import { path, .. } if path == "Toybox.Lang"));
+    assert!(matches!(&statements[1], MonkeyCStatement::Using { path, alias: Some(alias), .. }
+        if path == "Toybox.Graphics" && alias == "Gfx"));
+    assert!(matches!(&statements[2], MonkeyCStatement::Using { alias: None, .. }));
+
+    match &statements[3] {
+        MonkeyCStatement::ModuleDeclaration { name, annotations, children, .. } => {
+            assert_eq!(name, "Utils");
+            assert_eq!(annotation_names(annotations), vec!["glance"]);
+            assert_eq!(children.len(), 2);
+            match &children[0] {
+                MonkeyCStatement::EnumDeclaration { name: None, members, .. } => {
+                    let names: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+                    assert_eq!(names, vec!["A", "B", "C"]);
+                    assert!(members[0].value.is_some());
+                    assert!(members[1].value.is_none());
+                    assert!(members[2].value.is_some());
+                }
+                other => panic!("expected an enum, got {:?}", other),
+            }
+            assert!(matches!(&children[1], MonkeyCStatement::ModuleDeclaration { name, .. } if name == "Inner"));
+        }
+        other => panic!("expected a module, got {:?}", other),
+    }
+    match &statements[4] {
+        MonkeyCStatement::EnumDeclaration { name, members, modifiers, .. } => {
+            assert_eq!(name.as_deref(), Some("Direction"));
+            assert_eq!(members.len(), 2);
+            assert_eq!(modifiers, &vec![MonkeyCModifier::Hidden]);
+        }
+        other => panic!("expected an enum, got {:?}", other),
+    }
+}
+
+#[test]
+fn annotations_and_modifiers_of_modules_imports_and_enums() {
+    let statements = parse("(:debug) hidden enum { A }\n(:glance) module M {}\n(:background) using Toybox.Lang;\n\
+                            (:background) import Toybox.Time;");
+    match &statements[0] {
+        MonkeyCStatement::EnumDeclaration { annotations, modifiers, .. } => {
+            assert_eq!(annotation_names(annotations), vec!["debug"]);
+            assert_eq!(modifiers, &vec![MonkeyCModifier::Hidden]);
+        }
+        other => panic!("expected an enum, got {:?}", other),
+    }
+    assert!(matches!(&statements[1], MonkeyCStatement::ModuleDeclaration { annotations, .. }
+        if annotation_names(annotations) == vec!["glance"]));
+    assert!(matches!(&statements[2], MonkeyCStatement::Using { annotations, .. }
+        if annotation_names(annotations) == vec!["background"]));
+    assert!(matches!(&statements[3], MonkeyCStatement::Import { annotations, .. }
+        if annotation_names(annotations) == vec!["background"]));
+}
+
+#[test]
+fn modifiers_of_modules_and_imports_are_reported() {
+    let cases = [("static module M {}", "'static' isn't allowed on modules"),
+                 ("hidden using Toybox.Lang;", "'hidden' isn't allowed on using statements"),
+                 ("public import Toybox.Lang;", "'public' isn't allowed on imports")];
+    for (source, message) in cases.iter() {
+        let (statements, errors) = parse_with_errors(source);
+        assert_eq!(errors.len(), 1, "{}", source);
+        assert_eq!(errors[0].msg, *message, "{}", source);
+        assert_eq!(&source[errors[0].span.start..errors[0].span.end], source.split(' ').next().unwrap());
+        // The modifier is still in the tree
+        match &statements[0] {
+            MonkeyCStatement::ModuleDeclaration { modifiers, .. } |
+            MonkeyCStatement::Using { modifiers, .. } |
+            MonkeyCStatement::Import { modifiers, .. } => assert_eq!(modifiers.len(), 1, "{}", source),
+            other => panic!("expected a module or an import, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn malformed_modules_imports_and_enums_are_reported() {
+    let cases = [("using Toybox.Graphics as;", "Expected an identifier, found ';'"),
+                 ("import Toybox.Lang", "Expected ';', found end of file"),
+                 ("module { }", "Expected a module name, found '{'"),
+                 ("module A { 1 }", "Expected a module member, found '1'"),
+                 ("enum { A B }", "Expected ',' or '}', found 'B'"),
+                 ("enum { A = }", "Expected an expression, found '}'")];
+    for (source, message) in cases.iter() {
+        let (_, errors) = parse_with_errors(source);
+        assert_eq!(errors[0].msg, *message, "{}", source);
+    }
+}
+
+#[test]
+fn deeply_nested_declarations_are_reported() {
+    let source = format!("{}{}", "class A {".repeat(10_000), "}".repeat(10_000));
+    let (statements, errors) = parse_with_errors(&source);
+    assert!(matches!(statements[..], [MonkeyCStatement::ClassDeclaration { .. }]), "{:?}", statements);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].msg, "Declaration is nested too deeply");
+}
@@ -0,0 +1,166 @@
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use crate::parser::ast::{MonkeyCExpression, MonkeyCStatement};
+use crate::span::Span;
+use crate::tests::types::show_type;
+use std::path::PathBuf;
+
+/// Parses `source` as the value of a variable
+fn parse_value(source: &str) -> MonkeyCExpression {
+    let data = format!("var x = {};", source);
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let mut parser = MonkeyCParser::new(tokens, PathBuf::from("<test>"));
+    let (mut statements, errors) = parser.parse();
+    assert!(errors.is_empty(), "{:?}", errors);
+    match statements.pop() {
+        Some(MonkeyCStatement::VariableDeclaration { mut declarators, .. }) => declarators.remove(0).default_val.unwrap(),
+        other => panic!("expected a variable declaration, got {:?}", other),
+    }
+}
+
+/// Writes the expression as an S-expression, so
+/// the shape of the tree is easy to compare
+pub(super) fn sexpr(expression: &MonkeyCExpression) -> String {
+    let list = |items: &[MonkeyCExpression]| items.iter().map(sexpr).collect::<Vec<_>>().join(" ");
+    match expression {
+        MonkeyCExpression::Simple { value, .. } => value.clone(),
+        MonkeyCExpression::Reference { name, .. } => name.clone(),
+        MonkeyCExpression::Mathematical { lhs, op, rhs, .. } => format!("({:?} {} {})", op, sexpr(lhs), sexpr(rhs)),
+        MonkeyCExpression::Binary { lhs, op, rhs, .. } => format!("({:?} {} {})", op, sexpr(lhs), sexpr(rhs)),
+        MonkeyCExpression::Bitwise { lhs, op, rhs, .. } => format!("({:?} {} {})", op, sexpr(lhs), sexpr(rhs)),
+        MonkeyCExpression::Logical { lhs, op, rhs, .. } => format!("({:?} {} {})", op, sexpr(lhs), sexpr(rhs)),
+        MonkeyCExpression::Unary { op, operand, .. } => format!("({:?} {})", op, sexpr(operand)),
+        MonkeyCExpression::Update { op, operand, is_prefix, .. } =>
+            format!("({:?}{} {})", op, if *is_prefix { "Prefix" } else { "" }, sexpr(operand)),
+        MonkeyCExpression::Ternary { condition, if_true, if_false, .. } =>
+            format!("(? {} {} {})", sexpr(condition), sexpr(if_true), sexpr(if_false)),
+        MonkeyCExpression::Elvis { lhs, rhs, .. } => format!("(?: {} {})", sexpr(lhs), sexpr(rhs)),
+        MonkeyCExpression::Assignment { target, op, value, .. } => format!("(={:?} {} {})", op, sexpr(target), sexpr(value)),
+        MonkeyCExpression::Cast { value, target_type, .. } => format!("(as {} {})", sexpr(value), show_type(target_type)),
+        MonkeyCExpression::InstanceOf { value, class, .. } => format!("(instanceof {} {})", sexpr(value), sexpr(class)),
+        MonkeyCExpression::Has { value, member, .. } => format!("(has {} {})", sexpr(value), sexpr(member)),
+        MonkeyCExpression::Member { object, name, .. } => format!("(. {} {})", sexpr(object), name),
+        MonkeyCExpression::Index { object, index, .. } => format!("([] {} {})", sexpr(object), sexpr(index)),
+        MonkeyCExpression::Call { callee, args, .. } => format!("(call {} [{}])", sexpr(callee), list(args)),
+        MonkeyCExpression::New { class, args, .. } => format!("(new {} [{}])", sexpr(class), list(args)),
+        MonkeyCExpression::NewArray { size, is_byte_array, .. } =>
+            format!("(new-array{} {})", if *is_byte_array { "b" } else { "" }, sexpr(size)),
+        MonkeyCExpression::Array { elements, is_byte_array, .. } =>
+            format!("[{}]{}", list(elements), if *is_byte_array { "b" } else { "" }),
+        MonkeyCExpression::Dictionary { entries, .. } => format!("{{{}}}", entries.iter()
+            .map(|(k, v)| format!("{} => {}", sexpr(k), sexpr(v))).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+#[test]
+fn binary_precedence_and_associativity() {
+    let cases = [
+        ("1 + 2 * 3", "(Add 1 (Multiply 2 3))"),
+        ("1 - 2 - 3", "(Subtract (Subtract 1 2) 3)"),
+        ("(1 + 2) * 3", "(Multiply (Add 1 2) 3)"),
+        ("a << 2 + 1", "(Add (LeftShift a 2) 1)"),
+        ("a | b & c", "(Or a (And b c))"),
+        ("a + b < c * d", "(LessThan (Add a b) (Multiply c d))"),
+        ("a == b && c != d", "(And (Equals a b) (NotEquals c d))"),
+        ("a || b && c", "(Or a (And b c))"),
+        ("a or b and not_c", "(Or a (And b not_c))"),
+        ("x instanceof Lang.String", "(instanceof x (. Lang String))"),
+        ("Attention has :vibrate", "(has Attention vibrate)"),
+        ("a + b as Number", "(Add a (as b Number))"),
+        ("d[\"k\"] as Array<Number>? == null", "(Equals (as ([] d k) Array<Number>?) null)"),
+    ];
+    for (source, expected) in cases.iter() {
+        assert_eq!(sexpr(&parse_value(source)), *expected, "{}", source);
+    }
+}
+
+#[test]
+fn unary_and_ternary() {
+    let cases = [
+        ("-a * b", "(Multiply (Negate a) b)"),
+        ("!a && ~b", "(And (Not a) (BitwiseNot b))"),
+        ("- -1", "(Negate (Negate 1))"),
+        ("i++", "(Increment i)"),
+        ("--i", "(DecrementPrefix i)"),
+        ("a ? b : c ? d : e", "(? a b (? c d e))"),
+        ("a > 0 ? :pos : :neg", "(? (GreaterThan a 0) pos neg)"),
+        ("a ?: b ?: c", "(?: a (?: b c))"),
+        // After a cast, `?` only makes the type nullable if no expression follows it
+        ("x as Boolean ? 1 : 2", "(? (as x Boolean) 1 2)"),
+        ("x as Boolean? ? 1 : 2", "(? (as x Boolean?) 1 2)"),
+        ("x as Number or Null ? -y : :none", "(? (as x (Number or Null)) (Negate y) none)"),
+    ];
+    for (source, expected) in cases.iter() {
+        assert_eq!(sexpr(&parse_value(source)), *expected, "{}", source);
+    }
+}
+
+#[test]
+fn postfix_and_primary_expressions() {
+    let cases = [
+        ("Toybox.Graphics.COLOR_RED", "(. (. Toybox Graphics) COLOR_RED)"),
+        ("$.gState[0].name", "(. ([] (. $ gState) 0) name)"),
+        ("foo(1, bar(2), \"s\")", "(call foo [1 (call bar [2]) s])"),
+        ("self.method(:onTimer)", "(call (. self method) [onTimer])"),
+        ("view.getData()[i + 1]", "([] (call (. view getData) []) (Add i 1))"),
+        ("new Timer.Timer()", "(new (. Timer Timer) [])"),
+        ("new Foo(1, 2).run()", "(call (. (new Foo [1 2]) run) [])"),
+        ("new [10]", "(new-array 10)"),
+        ("new [n * 2]b", "(new-arrayb (Multiply n 2))"),
+        ("[1, 2, [3],]", "[1 2 [3]]"),
+        ("[0x01, 0x02]b", "[0x01 0x02]b"),
+        ("{ :a => 1, \"b\" => [true, null], }", "{a => 1, b => [true null]}"),
+        ("{}", "{}"),
+    ];
+    for (source, expected) in cases.iter() {
+        assert_eq!(sexpr(&parse_value(source)), *expected, "{}", source);
+    }
+}
+
+#[test]
+fn expression_spans_include_brackets() {
+    // "var x = " is 8 bytes long
+    let expression = parse_value("(a + b) * foo(c)");
+    assert_eq!(expression.span(), Span::new(0, 8, 24));
+    match expression {
+        MonkeyCExpression::Mathematical { lhs, rhs, .. } => {
+            assert_eq!(lhs.span(), Span::new(0, 9, 14));
+            assert_eq!(rhs.span(), Span::new(0, 18, 24));
+        }
+        other => panic!("expected a multiplication, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_expressions_are_reported() {
+    let cases = [("var x = 1 +;", "Expected an expression, found ';'"),
+                 ("var x = foo(1 2);", "Expected ',' or ')', found '2'"),
+                 ("var x = a ? b;", "Expected ':', found ';'"),
+                 ("var x = { :a 1 };", "Expected '=>', found '1'"),
+                 ("var x = new 5;", "Expected a class name, found '5'"),
+                 ("var x = (1", "Expected ')', found end of file")];
+    for (source, message) in cases.iter() {
+        let mut lexer = MonkeyCLexer::new(source.chars().collect());
+        let (tokens, _) = lexer.lex();
+        let mut parser = MonkeyCParser::new(tokens, PathBuf::from("<test>"));
+        let (_, errors) = parser.parse();
+        assert_eq!(errors[0].msg, *message, "{}", source);
+    }
+}
+
+#[test]
+fn deeply_nested_expressions_are_reported() {
+    let nested = |depth: usize| format!("var x = {}1{};", "(".repeat(depth), ")".repeat(depth));
+    assert_eq!(sexpr(&parse_value(&format!("{}1{}", "(".repeat(50), ")".repeat(50)))), "1");
+
+    for source in [nested(10_000), format!("var x = {}1;", "-".repeat(10_000)), format!("var x as {}Number;", "Array<".repeat(10_000))].iter() {
+        let mut lexer = MonkeyCLexer::new(source.chars().collect());
+        let (tokens, _) = lexer.lex();
+        let (statements, errors) = MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse();
+        assert!(matches!(statements[..], [MonkeyCStatement::Error { .. }]), "{:?}", statements);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].msg.ends_with("is nested too deeply"), "{}", errors[0].msg);
+    }
+}
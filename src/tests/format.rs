@@ -0,0 +1,198 @@
+use crate::config::{BraceStyle, Config, FormatConfig};
+use crate::fmt::{diff, format_source, same_tree, FormatError};
+use crate::DiagnosticKind;
+use std::path::Path;
+
+fn format(source: &str, config: &FormatConfig) -> String {
+    let formatted = format_source(source, config).unwrap();
+    // Formatting is expected to settle after one run
+    assert_eq!(format_source(&formatted, config).unwrap(), formatted);
+    formatted
+}
+
+#[test]
+fn config_is_read_from_toml() {
+    assert_eq!(Config::from_toml("").unwrap(), Config::default());
+
+    let config = Config::from_toml("[format]\nindent_width = 2\nbrace_style = \"next_line\"\n").unwrap();
+    assert_eq!(config.format.indent_width, 2);
+    assert_eq!(config.format.brace_style, BraceStyle::NextLine);
+    assert_eq!(config.format.max_line_width, FormatConfig::default().max_line_width);
+
+    assert!(Config::from_toml("[format]\nbrace_style = \"sideways\"\n").is_err());
+    assert!(Config::from_toml("[format]\nindent = 2\n").is_err());
+}
+
+#[test]
+fn comments_and_blank_lines_are_kept() {
+    let source = "\
+// Header
+
+
+using Toybox.Graphics;
+class A { // the class
+  /// Doc
+  var x = 1; // counter
+
+  /* after a blank line */ var y = foo(1, /* one */ 2);
+  enum { ONE, // first
+    TWO }
+  function f() {
+    // nothing yet
+  }
+  // the end
+}
+// last words
+";
+    let expected = "\
+// Header
+
+using Toybox.Graphics;
+
+class A {
+    // the class
+    /// Doc
+    var x = 1; // counter
+
+    /* after a blank line */
+    var y = foo(1, 2); /* one */
+    enum {
+        ONE, // first
+        TWO
+    }
+
+    function f() {
+        // nothing yet
+    }
+    // the end
+}
+// last words
+";
+    assert_eq!(format(source, &FormatConfig::default()), expected);
+}
+
+#[test]
+fn comments_before_else_do_not_add_blank_lines() {
+    let source = "function f() {\n    if (x) {\n        y();\n    } // after then\n    else { y(); }\n}\n";
+    let expected = "function f() {\n    if (x) {\n        y();\n    } else {\n        // after then\n        y();\n    }\n}\n";
+    assert_eq!(format(source, &FormatConfig::default()), expected);
+}
+
+#[test]
+fn layout_follows_the_config() {
+    let config = FormatConfig { indent_width: 2, brace_style: BraceStyle::NextLine, max_line_width: 30 };
+    let source = "function f(a, b) { if (a) { g(a); } else { h(\"a long argument\", [1, 2, 3], b); } try { g(b); } catch (e) { } }";
+    let expected = "\
+function f(a, b)
+{
+  if (a)
+  {
+    g(a);
+  }
+  else
+  {
+    h(
+      \"a long argument\",
+      [1, 2, 3],
+      b
+    );
+  }
+  try
+  {
+    g(b);
+  }
+  catch (e)
+  {}
+}
+";
+    assert_eq!(format(source, &config), expected);
+}
+
+#[test]
+fn long_lists_are_split() {
+    let config = FormatConfig { max_line_width: 30, ..FormatConfig::default() };
+    let source = "function draw(dc as Dc, x as Number, y as Number) { return { :x => [x, x + 1, x + 2, x + 3], :y => y }; }";
+    let expected = "\
+function draw(
+    dc as Dc,
+    x as Number,
+    y as Number
+) {
+    return {
+        :x => [
+            x,
+            x + 1,
+            x + 2,
+            x + 3
+        ],
+        :y => y
+    };
+}
+";
+    assert_eq!(format(source, &config), expected);
+}
+
+#[test]
+fn files_with_errors_are_not_formatted() {
+    let config = FormatConfig::default();
+    let kinds = |source: &str| match format_source(source, &config) {
+        Err(FormatError::Syntax(errors)) => errors.iter().map(|e| e.kind).collect::<Vec<_>>(),
+        other => panic!("expected syntax errors, got {:?}", other),
+    };
+    assert_eq!(kinds("var x = \"open;")[0], DiagnosticKind::BadToken);
+    assert_eq!(kinds("var x = ;")[0], DiagnosticKind::SyntaxError);
+}
+
+#[test]
+fn annotations_and_modifiers_are_kept() {
+    let source = "(:debug) hidden enum { A }\n(:glance) module M { }\n(:background) using Toybox.Lang;\n";
+    let expected = "(:debug)\nhidden enum {\n    A\n}\n\n(:glance)\nmodule M {}\n\n(:background)\nusing Toybox.Lang;\n";
+    assert_eq!(format(source, &FormatConfig::default()), expected);
+}
+
+#[test]
+fn changes_of_meaning_are_noticed() {
+    let parse = |source: &str| crate::parse(source).0;
+    assert!(!same_tree(&parse("(:debug) enum { A }"), &parse("enum { A }")));
+    assert!(!same_tree(&parse("var x = 1;"), &parse("var x = 2;")));
+    assert!(same_tree(&parse("var x =\n1;"), &parse("var x = 1;")));
+}
+
+#[test]
+fn diff_shows_changed_lines_with_context() {
+    let original = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+    let formatted = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nK\nl\nm\n";
+    let expected = "\
+--- x.mc
++++ x.mc
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -8,5 +8,6 @@
+ h
+ i
+ j
+-k
++K
+ l
++m
+";
+    assert_eq!(diff(Path::new("x.mc"), original, formatted), expected);
+    assert_eq!(diff(Path::new("x.mc"), original, original), "");
+
+    // Changed line breaks and a missing one at the end show up too
+    let expected = "--- x.mc\n+++ x.mc\n@@ -1,2 +1,2 @@\n-a\r\n+a\n b\n\\ No newline at end of file\n";
+    assert_eq!(diff(Path::new("x.mc"), "a\r\nb", "a\nb"), expected);
+}
+
+#[test]
+fn line_breaks_of_the_file_are_kept() {
+    let source = "/* a\r\n   b */\r\nvar x = 1;\r\nfunction f() { }\r\n";
+    let expected = "/* a\r\n   b */\r\nvar x = 1;\r\n\r\nfunction f() {}\r\n";
+    assert_eq!(format(source, &FormatConfig::default()), expected);
+    assert_eq!(diff(Path::new("x.mc"), &format("var x=1;\r\n", &FormatConfig::default()), "var x = 1;\r\n"), "");
+}
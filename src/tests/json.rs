@@ -0,0 +1,63 @@
+use crate::cst;
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[test]
+fn statements_serialize_with_their_type() {
+    let source = "(:debug) const A as Number = -b;";
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, _) = lexer.lex();
+    let (statements, errors) = MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let span = |start: usize, end: usize| json!({ "file_id": 0, "start": start, "end": end });
+    assert_eq!(json!(statements), json!([{
+        "type": "VariableDeclaration",
+        "declarators": [{
+            "name": "A",
+            "var_type": { "type": "Named", "name": "Number", "generics": [], "span": span(20, 26) },
+            "default_val": {
+                "type": "Unary",
+                "op": "Negate",
+                "operand": { "type": "Reference", "name": "b", "span": span(30, 31) },
+                "span": span(29, 31)
+            },
+            "span": span(15, 31)
+        }],
+        "is_const": true,
+        "annotations": [{ "name": "debug", "args": [], "span": span(1, 7) }],
+        "modifiers": [],
+        "span": span(0, 32)
+    }]));
+}
+
+#[test]
+fn tokens_and_syntax_trees_serialize() {
+    let source = "f(1); // call";
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, _) = lexer.lex();
+    assert_eq!(json!(tokens[2]), json!({
+        "kind": "IntLiteral",
+        "literal": "1",
+        "row": 1,
+        "column": 3,
+        "span": { "file_id": 0, "start": 2, "end": 3 },
+        "value": { "Int": 1 },
+        "leading_trivia": [],
+        "trailing_trivia": []
+    }));
+
+    let root = json!(cst::build(source, &tokens));
+    assert_eq!(root["kind"], "SourceFile");
+    assert_eq!(root["full_range"], json!({ "start": 0, "end": source.len() }));
+    let group = &root["children"][1];
+    assert_eq!(group["kind"], "BraceGroup");
+    assert_eq!(group["children"][1]["text"], "1");
+    assert_eq!(group["children"][1]["text_range"], json!({ "start": 2, "end": 3 }));
+    let semicolon = &root["children"][2];
+    assert_eq!(semicolon["trailing_trivia"], json!([
+        { "kind": "Whitespace", "text": " " },
+        { "kind": "LineComment", "text": "// call" }
+    ]));
+}
@@ -0,0 +1,235 @@
+use crate::lexer::MonkeyCLexer;
+use crate::lexer::tokens::{TokenKind, TokenValue, TriviaKind};
+
+#[test]
+fn comments_are_attached_as_trivia() {
+    let data = "// Doc comment\n/* block\n comment */ var x = a / b; // trailing\nvar y;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+    assert_eq!(kinds, vec![TokenKind::Var, TokenKind::Identifier, TokenKind::Assign, TokenKind::Identifier, TokenKind::Slash,
+                           TokenKind::Identifier, TokenKind::Semicolon, TokenKind::Var, TokenKind::Identifier, TokenKind::Semicolon, TokenKind::Eof]);
+
+    let leading = &tokens[0].leading_trivia;
+    let leading_kinds: Vec<TriviaKind> = leading.iter().map(|t| t.kind.clone()).collect();
+    assert_eq!(leading_kinds, vec![TriviaKind::LineComment, TriviaKind::Newline, TriviaKind::BlockComment, TriviaKind::Whitespace]);
+    assert_eq!(leading[0].text, "// Doc comment");
+    assert_eq!((leading[0].row, leading[0].column), (1, 1));
+    assert_eq!(leading[2].text, "/* block\n comment */");
+    assert_eq!((leading[2].row, leading[2].column), (2, 1));
+    assert_eq!((tokens[0].row, tokens[0].column), (3, 13));
+
+    let trailing: Vec<&str> = tokens[6].trailing_trivia.iter().map(|t| t.text.as_str()).collect();
+    assert_eq!(trailing, vec![" ", "// trailing", "\n"]);
+    assert!(tokens[7].leading_trivia.is_empty());
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let data = "var x;\n  /* never closed";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert_eq!(tokens.len(), 4);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].msg, "Unterminated block comment");
+    assert_eq!(errors[0].at, (2, 3));
+    assert_eq!(tokens[3].kind, TokenKind::Eof);
+    assert_eq!(tokens[3].leading_trivia[1].text, "/* never closed");
+}
+
+#[test]
+fn operators_use_maximal_munch() {
+    let data = "a<<=b>>=c==d!=e<=f>=g&&h||i<<j>>k+=l-=m*=n/=o%=p&=q|=r^=s++t--u?: v*w%x<y>z;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let operators: Vec<TokenKind> = tokens.iter()
+        .filter(|t| t.kind != TokenKind::Identifier)
+        .map(|t| t.kind.clone())
+        .collect();
+    assert_eq!(operators, vec![TokenKind::LeftShiftAssign, TokenKind::RightShiftAssign, TokenKind::Equals, TokenKind::NotEquals,
+                               TokenKind::LessThanEquals, TokenKind::GreaterThanEquals, TokenKind::DoubleAmpersand,
+                               TokenKind::DoubleVerticalBar, TokenKind::LeftShift, TokenKind::RightShift, TokenKind::PlusAssign,
+                               TokenKind::MinusAssign, TokenKind::AsteriskAssign, TokenKind::SlashAssign, TokenKind::PercentAssign,
+                               TokenKind::AmpersandAssign, TokenKind::VerticalBarAssign, TokenKind::CaretAssign, TokenKind::Increment,
+                               TokenKind::Decrement, TokenKind::Elvis, TokenKind::Asterisk, TokenKind::Percent, TokenKind::LessThan,
+                               TokenKind::GreaterThan, TokenKind::Semicolon, TokenKind::Eof]);
+    assert_eq!(tokens[1].literal, "<<=");
+    assert_eq!((tokens[3].row, tokens[3].column), (1, 6));
+}
+
+#[test]
+fn numeric_literals() {
+    let data = "1 0x1F 1e5 1.5 1.5f 2.5d 5d 0xFFl 7l 017 0xFFFFFFFF 1.5e-3 5.format;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let values: Vec<(TokenKind, Option<TokenValue>)> = tokens.iter().take(12).map(|t| (t.kind.clone(), t.value.clone())).collect();
+    assert_eq!(values, vec![
+        (TokenKind::IntLiteral, Some(TokenValue::Int(1))),
+        (TokenKind::IntLiteral, Some(TokenValue::Int(31))),
+        (TokenKind::FloatLiteral, Some(TokenValue::Float(1e5))),
+        (TokenKind::FloatLiteral, Some(TokenValue::Float(1.5))),
+        (TokenKind::FloatLiteral, Some(TokenValue::Float(1.5))),
+        (TokenKind::DoubleLiteral, Some(TokenValue::Double(2.5))),
+        (TokenKind::DoubleLiteral, Some(TokenValue::Double(5.0))),
+        (TokenKind::LongLiteral, Some(TokenValue::Long(255))),
+        (TokenKind::LongLiteral, Some(TokenValue::Long(7))),
+        (TokenKind::IntLiteral, Some(TokenValue::Int(15))),
+        (TokenKind::IntLiteral, Some(TokenValue::Int(-1))),
+        (TokenKind::FloatLiteral, Some(TokenValue::Float(1.5e-3))),
+    ]);
+    assert_eq!(tokens[1].literal, "0x1F");
+    assert_eq!(tokens[12].literal, "5");
+}
+
+#[test]
+fn malformed_numeric_literals_do_not_stop_lexing() {
+    let data = "12abc 1.5l 09 0x 5ld 99999999999;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+
+    assert_eq!(tokens.len(), 8);
+    assert!(tokens.iter().all(|t| t.value.is_none()));
+    let positions: Vec<((u64, u64), usize)> = errors.iter().map(|e| (e.at, e.span.end - e.span.start)).collect();
+    assert_eq!(positions, vec![((1, 1), 5), ((1, 7), 4), ((1, 12), 2), ((1, 15), 2), ((1, 18), 3), ((1, 22), 11)]);
+    assert_eq!(errors[0].msg, "Invalid suffix 'abc' on a numeric literal");
+    assert_eq!(errors[4].msg, "Multiple number decorators are forbidden");
+}
+
+#[test]
+fn numeric_literals_out_of_range() {
+    let data = "2147483647 2147483648 3000000000 0x80000000 9223372036854775807l 9223372036854775808l 1e38 1e39 1e999d";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+
+    let values: Vec<Option<TokenValue>> = tokens.iter().take(9).map(|t| t.value.clone()).collect();
+    assert_eq!(values, vec![
+        Some(TokenValue::Int(i32::MAX)),
+        None,
+        None,
+        Some(TokenValue::Int(i32::MIN)),
+        Some(TokenValue::Long(i64::MAX)),
+        None,
+        Some(TokenValue::Float(1e38)),
+        None,
+        None,
+    ]);
+    let messages: Vec<&str> = errors.iter().map(|e| e.msg.as_str()).collect();
+    assert_eq!(messages, vec![
+        "Integer literal is too large, add an 'l' to make it a Long",
+        "Integer literal is too large, add an 'l' to make it a Long",
+        "Integer literal is too large for a Long",
+        "Float literal is out of range",
+        "Double literal is out of range",
+    ]);
+}
+
+#[test]
+fn string_and_char_escapes() {
+    let data = r#"var s = "a\"b\n\u0041\uD83D\uDE00"; var c = '\n'; var d = 'A'; var e = '\'';"#;
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let literals: Vec<(TokenKind, String, Option<TokenValue>)> = tokens.iter()
+        .filter(|t| t.kind == TokenKind::StringLiteral || t.kind == TokenKind::CharLiteral)
+        .map(|t| (t.kind.clone(), t.literal.clone(), t.value.clone()))
+        .collect();
+    assert_eq!(literals, vec![
+        (TokenKind::StringLiteral, r#"a\"b\n\u0041\uD83D\uDE00"#.to_string(), Some(TokenValue::Str("a\"b\nA\u{1F600}".to_string()))),
+        (TokenKind::CharLiteral, r"\n".to_string(), Some(TokenValue::Char('\n'))),
+        (TokenKind::CharLiteral, "A".to_string(), Some(TokenValue::Char('A'))),
+        (TokenKind::CharLiteral, r"\'".to_string(), Some(TokenValue::Char('\''))),
+    ]);
+}
+
+#[test]
+fn bad_string_and_char_literals() {
+    let data = "var s = \"bad \\q escape\";\nvar t = \"never closed\nvar c = 'ab';\nvar d = '\\uD800';";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+
+    let errors: Vec<(&str, (u64, u64), usize)> = errors.iter().map(|e| (e.msg.as_str(), e.at, e.span.end - e.span.start)).collect();
+    assert_eq!(errors, vec![
+        ("Unknown escape sequence '\\q'", (1, 14), 2),
+        ("Unterminated String literal", (2, 9), 13),
+        ("A Char literal must contain exactly one character", (3, 9), 4),
+        ("Unpaired surrogate in Unicode escape '\\uD800'", (4, 10), 6),
+    ]);
+    // The line after an unterminated String is still lexed
+    assert!(tokens.iter().any(|t| t.kind == TokenKind::Var && t.row == 3));
+    assert!(tokens.iter().filter(|t| t.kind == TokenKind::StringLiteral).all(|t| t.value.is_none()));
+
+    // A lone `\r` ends a line as well
+    let data = "var s = \"open\rvar t;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert_eq!(errors.iter().map(|e| (e.msg.as_str(), e.at)).collect::<Vec<_>>(), vec![("Unterminated String literal", (1, 9))]);
+    assert!(tokens.iter().any(|t| t.kind == TokenKind::Var && (t.row, t.column) == (2, 1)));
+}
+
+#[test]
+fn punctuation() {
+    let data = "Toybox.Graphics; [1, 2]; { :a => 1 }; a ? b : c; @Rez; $.x;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let kinds: Vec<TokenKind> = tokens.iter()
+        .map(|t| t.kind.clone())
+        .filter(|k| !matches!(k, TokenKind::Identifier | TokenKind::IntLiteral | TokenKind::Semicolon | TokenKind::Comma))
+        .collect();
+    assert_eq!(kinds, vec![TokenKind::Dot, TokenKind::OpeningSquareBracket, TokenKind::ClosingSquareBracket,
+                           TokenKind::OpeningBracket, TokenKind::SymbolLiteral, TokenKind::Arrow, TokenKind::ClosingBracket,
+                           TokenKind::QuestionMark, TokenKind::Colon, TokenKind::At, TokenKind::Dollar, TokenKind::Dot, TokenKind::Eof]);
+}
+
+#[test]
+fn unknown_characters_are_reported() {
+    let data = "var x = 1 # 2;\nvar y = `;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+
+    let errors: Vec<(&str, (u64, u64))> = errors.iter().map(|e| (e.msg.as_str(), e.at)).collect();
+    assert_eq!(errors, vec![("Unknown character '#'", (1, 11)), ("Unknown character '`'", (2, 9))]);
+
+    // Lexing goes on after an unknown character, which becomes an error token
+    let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+    assert_eq!(kinds, vec![TokenKind::Var, TokenKind::Identifier, TokenKind::Assign, TokenKind::IntLiteral, TokenKind::Error,
+                           TokenKind::IntLiteral, TokenKind::Semicolon, TokenKind::Var, TokenKind::Identifier, TokenKind::Assign,
+                           TokenKind::Error, TokenKind::Semicolon, TokenKind::Eof]);
+}
+
+#[test]
+fn symbol_literals() {
+    let data = "method(:onTimer); (:background) Rez.Strings.:AppName; x ? :a : :b; y ? b :c; z ?:d :e; f ?: g; case 1:\n";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let symbols: Vec<(&str, (u64, u64))> = tokens.iter()
+        .filter(|t| t.kind == TokenKind::SymbolLiteral)
+        .map(|t| (t.literal.as_str(), (t.row, t.column)))
+        .collect();
+    assert_eq!(symbols, vec![("onTimer", (1, 8)), ("background", (1, 20)), ("AppName", (1, 45)),
+                             ("a", (1, 59)), ("b", (1, 64)), ("d", (1, 81))]);
+
+    let colons = tokens.iter().filter(|t| t.kind == TokenKind::Colon).count();
+    assert_eq!(colons, 4);
+    assert_eq!(tokens.iter().filter(|t| t.kind == TokenKind::Elvis).count(), 1);
+}
+
+#[test]
+fn identifiers_with_digits_and_underscores() {
+    let data = "var _my_var2 = x";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let literals: Vec<&str> = tokens.iter().map(|t| t.literal.as_str()).collect();
+    assert_eq!(literals, vec!["var", "_my_var2", "=", "x", ""]);
+}
@@ -1 +1,15 @@
-mod basic_parsing;
\ No newline at end of file
+mod basic_parsing;
+mod lexing;
+mod spans;
+mod robustness;
+mod cst;
+mod expressions;
+mod declarations;
+mod statements;
+mod types;
+mod recovery;
+mod visit;
+mod json;
+mod printer;
+mod format;
+mod api;
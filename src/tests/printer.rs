@@ -0,0 +1,300 @@
+use crate::fmt::same_tree;
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use crate::parser::ast::*;
+use crate::parser::printer::print;
+use crate::span::Span;
+use proptest::prelude::*;
+use std::path::PathBuf;
+
+fn parse(source: &str) -> Vec<MonkeyCStatement> {
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}\n{}", errors, source);
+    let (statements, errors) = MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse();
+    assert!(errors.is_empty(), "{:?}\n{}", errors, source);
+    statements
+}
+
+/// Prints `statements`, parses them back and checks that
+/// nothing changed. Returns the printed code.
+fn assert_round_trip(statements: &[MonkeyCStatement]) -> String {
+    let printed = print(statements);
+    let parsed = parse(&printed);
+    assert!(same_tree(&parsed, statements), "{}\n{:?}\n{:?}", printed, parsed, statements);
+    printed
+}
+
+#[test]
+fn prints_canonical_code() {
+    let source = "using Toybox.Graphics as Gfx;import Toybox.Lang;\n\
+                  (:background,:test) class Foo extends Lang.Object{hidden var a as Array<Number>?=[1,2]b,b;\n\
+                  static const C={:x=>1};enum Dir{UP,DOWN=2}\n\
+                  function f(x as Number or Null,cb as Method(y)as Void)as Boolean{if(x==null){return false;}else if(x>1)\n\
+                  {x-=1;}else{x++;}\n\
+                  for(var i=0;i<x;i++){continue;}while(true){break;}do{g();}while(false);\n\
+                  switch(x){case 1: case 2: h();break;default: return true;}\n\
+                  try{throw new Lang.Exception();}catch(e instanceof Lang.Exception){}catch(e){}finally{}\n\
+                  return (x as Number) < 2 && -(a ? b : c) > 0;}}";
+    let expected = "\
+using Toybox.Graphics as Gfx;
+import Toybox.Lang;
+
+(:background, :test)
+class Foo extends Lang.Object {
+    hidden var a as Array<Number>? = [1, 2]b, b;
+    static const C = {:x => 1};
+    enum Dir {
+        UP,
+        DOWN = 2
+    }
+
+    function f(x as Number or Null, cb as Method(y) as Void) as Boolean {
+        if (x == null) {
+            return false;
+        } else if (x > 1) {
+            x -= 1;
+        } else {
+            x++;
+        }
+        for (var i = 0; i < x; i++) {
+            continue;
+        }
+        while (true) {
+            break;
+        }
+        do {
+            g();
+        } while (false);
+        switch (x) {
+            case 1:
+            case 2:
+                h();
+                break;
+            default:
+                return true;
+        }
+        try {
+            throw new Lang.Exception();
+        } catch (e instanceof Lang.Exception) {} catch (e) {} finally {}
+        return (x as Number) < 2 && -(a ? b : c) > 0;
+    }
+}
+";
+    assert_eq!(assert_round_trip(&parse(source)), expected);
+}
+
+#[test]
+fn statements_without_blocks_are_indented() {
+    let source = "function f() { if (a) b(); else if (c) for (;;) d(); else while (e) {} do f(); while (g); }";
+    let expected = "\
+function f() {
+    if (a)
+        b();
+    else if (c)
+        for (;;)
+            d();
+    else
+        while (e) {}
+    do
+        f();
+    while (g);
+}
+";
+    assert_eq!(assert_round_trip(&parse(source)), expected);
+}
+
+#[test]
+fn brackets_are_added_where_needed() {
+    let cases = [
+        ("(a + b) * c", "(a + b) * c"),
+        ("a - (b - c)", "a - (b - c)"),
+        ("(a - b) - c", "a - b - c"),
+        ("(a ? b : c) ? d : e", "(a ? b : c) ? d : e"),
+        ("a ? b : (c ? d : e)", "a ? b : c ? d : e"),
+        ("-(-a)", "- -a"),
+        ("-(--a)", "- --a"),
+        ("(-a).b", "(-a).b"),
+        ("(a as Number) < b", "(a as Number) < b"),
+        ("(c + a as Number) < b", "(c + a as Number) < b"),
+        ("(x < a as Number) ? 1 : 2", "(x < a as Number) ? 1 : 2"),
+        ("-(a as Number)", "-(a as Number)"),
+        ("a or b and c", "a || b && c"),
+        ("(a || b) && c", "(a || b) && c"),
+        ("new Foo(1).bar[0]", "new Foo(1).bar[0]"),
+    ];
+    for (source, expected) in cases.iter() {
+        let statements = parse(&format!("var x = {};", source));
+        assert_eq!(assert_round_trip(&statements), format!("var x = {};\n", expected), "{}", source);
+    }
+}
+
+#[test]
+fn dangling_else_gets_a_block() {
+    let inner = MonkeyCStatement::If {
+        condition: reference("b"),
+        then_branch: Box::new(MonkeyCStatement::Return { value: None, span: Span::default() }),
+        else_branch: None,
+        span: Span::default()
+    };
+    let outer = MonkeyCStatement::If {
+        condition: reference("a"),
+        then_branch: Box::new(inner),
+        else_branch: Some(Box::new(MonkeyCStatement::Break { span: Span::default() })),
+        span: Span::default()
+    };
+    let printed = print(&[outer]);
+    assert_eq!(printed, "if (a) {\n    if (b)\n        return;\n} else\n    break;\n");
+}
+
+fn reference(name: &str) -> MonkeyCExpression {
+    MonkeyCExpression::Reference { name: name.to_string(), span: Span::default() }
+}
+
+fn boxed(expression: MonkeyCExpression) -> Box<MonkeyCExpression> {
+    Box::new(expression)
+}
+
+fn arb_type() -> impl Strategy<Value = MonkeyCTypeExpression> {
+    let named = prop::sample::select(vec!["Number", "String", "Lang.Object"])
+        .prop_map(|name| MonkeyCTypeExpression::Named { name: name.to_string(), generics: vec![], span: Span::default() });
+    named.prop_recursive(2, 4, 2, |inner| prop_oneof![
+        inner.clone().prop_map(|t| MonkeyCTypeExpression::Named { name: "Array".to_string(), generics: vec![t], span: Span::default() }),
+        inner.clone().prop_map(|t| MonkeyCTypeExpression::Nullable { inner: Box::new(t), span: Span::default() }),
+        prop::collection::vec(inner, 0..3).prop_map(|types| MonkeyCTypeExpression::Tuple { types, span: Span::default() }),
+    ])
+}
+
+fn arb_literal() -> impl Strategy<Value = MonkeyCExpression> {
+    prop_oneof![
+        (0u32..1000).prop_map(|n| (n.to_string(), MonkeyCLiteralKind::Int)),
+        Just(("2.5".to_string(), MonkeyCLiteralKind::Float)),
+        Just(("0x1Fl".to_string(), MonkeyCLiteralKind::Long)),
+        "[a-z ]{0,5}".prop_map(|s| (s, MonkeyCLiteralKind::String)),
+        Just(("c".to_string(), MonkeyCLiteralKind::Char)),
+        "[a-z][a-zA-Z]{0,4}".prop_map(|s| (s, MonkeyCLiteralKind::Symbol)),
+        Just(("true".to_string(), MonkeyCLiteralKind::Bool)),
+        Just(("null".to_string(), MonkeyCLiteralKind::Null)),
+    ].prop_map(|(value, kind)| MonkeyCExpression::Simple { value, kind, span: Span::default() })
+}
+
+fn arb_name() -> impl Strategy<Value = String> {
+    prop::sample::select(vec!["a", "b", "foo"]).prop_map(str::to_string)
+}
+
+fn arb_expression() -> impl Strategy<Value = MonkeyCExpression> {
+    let names = prop::sample::select(vec!["a", "b", "foo", "self", "$"]).prop_map(reference);
+    let leaf = prop_oneof![arb_literal(), names];
+    leaf.prop_recursive(4, 32, 3, |inner| {
+        let pair = (inner.clone(), inner.clone());
+        prop_oneof![
+            (pair.clone(), prop::sample::select(vec![MonkeyCExprMathOperation::Add, MonkeyCExprMathOperation::Subtract,
+                                                     MonkeyCExprMathOperation::Multiply, MonkeyCExprMathOperation::Modulo]))
+                .prop_map(|((lhs, rhs), op)| MonkeyCExpression::Mathematical { lhs: boxed(lhs), op, rhs: boxed(rhs), span: Span::default() }),
+            (pair.clone(), prop::sample::select(vec![MonkeyCExprBinaryOperation::LessThan, MonkeyCExprBinaryOperation::Equals,
+                                                     MonkeyCExprBinaryOperation::GreaterThanEquals]))
+                .prop_map(|((lhs, rhs), op)| MonkeyCExpression::Binary { lhs: boxed(lhs), op, rhs: boxed(rhs), span: Span::default() }),
+            (pair.clone(), prop::sample::select(vec![MonkeyCExprBitwiseOperation::LeftShift, MonkeyCExprBitwiseOperation::Or,
+                                                     MonkeyCExprBitwiseOperation::And]))
+                .prop_map(|((lhs, rhs), op)| MonkeyCExpression::Bitwise { lhs: boxed(lhs), op, rhs: boxed(rhs), span: Span::default() }),
+            (pair.clone(), prop::sample::select(vec![MonkeyCExprLogicalOperation::And, MonkeyCExprLogicalOperation::Or]))
+                .prop_map(|((lhs, rhs), op)| MonkeyCExpression::Logical { lhs: boxed(lhs), op, rhs: boxed(rhs), span: Span::default() }),
+            (inner.clone(), prop::sample::select(vec![MonkeyCExprUnaryOperation::Negate, MonkeyCExprUnaryOperation::Not,
+                                                      MonkeyCExprUnaryOperation::BitwiseNot]))
+                .prop_map(|(operand, op)| MonkeyCExpression::Unary { op, operand: boxed(operand), span: Span::default() }),
+            (inner.clone(), any::<bool>(), any::<bool>()).prop_map(|(operand, increment, is_prefix)| MonkeyCExpression::Update {
+                op: if increment { MonkeyCExprUpdateOperation::Increment } else { MonkeyCExprUpdateOperation::Decrement },
+                operand: boxed(operand), is_prefix, span: Span::default()
+            }),
+            (inner.clone(), inner.clone(), inner.clone()).prop_map(|(condition, if_true, if_false)| MonkeyCExpression::Ternary {
+                condition: boxed(condition), if_true: boxed(if_true), if_false: boxed(if_false), span: Span::default()
+            }),
+            pair.clone().prop_map(|(lhs, rhs)| MonkeyCExpression::Elvis { lhs: boxed(lhs), rhs: boxed(rhs), span: Span::default() }),
+            (inner.clone(), arb_type()).prop_map(|(value, target_type)| MonkeyCExpression::Cast {
+                value: boxed(value), target_type, span: Span::default()
+            }),
+            pair.clone().prop_map(|(value, class)| MonkeyCExpression::InstanceOf { value: boxed(value), class: boxed(class), span: Span::default() }),
+            pair.clone().prop_map(|(value, member)| MonkeyCExpression::Has { value: boxed(value), member: boxed(member), span: Span::default() }),
+            (inner.clone(), arb_name()).prop_map(|(object, name)| MonkeyCExpression::Member { object: boxed(object), name, span: Span::default() }),
+            pair.clone().prop_map(|(object, index)| MonkeyCExpression::Index { object: boxed(object), index: boxed(index), span: Span::default() }),
+            (inner.clone(), prop::collection::vec(inner.clone(), 0..3))
+                .prop_map(|(callee, args)| MonkeyCExpression::Call { callee: boxed(callee), args, span: Span::default() }),
+            prop::collection::vec(inner.clone(), 0..3)
+                .prop_map(|args| MonkeyCExpression::New { class: boxed(reference("Foo")), args, span: Span::default() }),
+            (inner.clone(), any::<bool>()).prop_map(|(size, is_byte_array)| MonkeyCExpression::NewArray {
+                size: boxed(size), is_byte_array, span: Span::default()
+            }),
+            (prop::collection::vec(inner.clone(), 0..3), any::<bool>()).prop_map(|(elements, is_byte_array)| MonkeyCExpression::Array {
+                elements, is_byte_array, span: Span::default()
+            }),
+            prop::collection::vec(pair, 0..3).prop_map(|entries| MonkeyCExpression::Dictionary { entries, span: Span::default() }),
+        ]
+    })
+}
+
+/// Statements that can appear inside of a function
+fn arb_statement() -> impl Strategy<Value = MonkeyCStatement> {
+    let target = prop_oneof![
+        arb_name().prop_map(|name| reference(&name)),
+        (arb_expression(), arb_name()).prop_map(|(object, name)| MonkeyCExpression::Member { object: boxed(object), name, span: Span::default() }),
+    ];
+    let leaf = prop_oneof![
+        arb_expression().prop_map(|expression| MonkeyCStatement::ExpressionStatement { expression, span: Span::default() }),
+        (target, arb_expression()).prop_map(|(target, value)| MonkeyCStatement::ExpressionStatement {
+            expression: MonkeyCExpression::Assignment {
+                target: boxed(target), op: MonkeyCExprAssignmentOperation::Add, value: boxed(value), span: Span::default()
+            },
+            span: Span::default()
+        }),
+        (arb_name(), prop::option::of(arb_type()), prop::option::of(arb_expression())).prop_map(|(name, var_type, default_val)| {
+            MonkeyCStatement::VariableDeclaration {
+                declarators: vec![MonkeyCVariableDeclarator { name, var_type, default_val, span: Span::default() }],
+                is_const: false, annotations: vec![], modifiers: vec![], span: Span::default()
+            }
+        }),
+        prop::option::of(arb_expression()).prop_map(|value| MonkeyCStatement::Return { value, span: Span::default() }),
+        Just(MonkeyCStatement::Break { span: Span::default() }),
+    ];
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        let block = prop::collection::vec(inner.clone(), 0..3).prop_map(|children| MonkeyCStatement::Block { children, span: Span::default() });
+        prop_oneof![
+            block.clone(),
+            // A branch without a block before an `else` could take it
+            (arb_expression(), block.clone(), prop::option::of(inner.clone())).prop_map(|(condition, then_branch, else_branch)| {
+                MonkeyCStatement::If { condition, then_branch: Box::new(then_branch), else_branch: else_branch.map(Box::new), span: Span::default() }
+            }),
+            (arb_expression(), inner.clone()).prop_map(|(condition, body)| MonkeyCStatement::While {
+                condition, body: Box::new(body), span: Span::default()
+            }),
+            (block.clone(), arb_expression()).prop_map(|(body, condition)| MonkeyCStatement::DoWhile {
+                body: Box::new(body), condition, span: Span::default()
+            }),
+            (prop::option::of(arb_expression()), prop::option::of(arb_expression()), inner.clone())
+                .prop_map(|(condition, update, body)| MonkeyCStatement::For {
+                    init: None, condition, update, body: Box::new(body), span: Span::default()
+                }),
+            (arb_expression(), prop::collection::vec((prop::option::of(arb_expression()), prop::collection::vec(inner, 0..2)), 0..3))
+                .prop_map(|(value, cases)| MonkeyCStatement::Switch {
+                    value,
+                    cases: cases.into_iter().map(|(value, children)| MonkeyCSwitchCase { value, children, span: Span::default() }).collect(),
+                    span: Span::default()
+                }),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn printed_code_parses_into_the_same_tree(body in prop::collection::vec(arb_statement(), 0..4)) {
+        let function = MonkeyCStatement::FunctionDeclaration {
+            name: "f".to_string(),
+            params: vec![],
+            return_type: None,
+            annotations: vec![],
+            modifiers: vec![],
+            body: Box::new(MonkeyCStatement::Block { children: body, span: Span::default() }),
+            span: Span::default()
+        };
+        assert_round_trip(&[function]);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use crate::parser::ast::MonkeyCStatement;
+use crate::parser::err::MCParseError;
+use std::path::PathBuf;
+
+fn parse(source: &str) -> (Vec<MonkeyCStatement>, Vec<MCParseError>) {
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, _) = lexer.lex();
+    MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse()
+}
+
+/// Names the kinds of `statements`, like "Error Var Function"
+fn kinds(statements: &[MonkeyCStatement]) -> String {
+    statements.iter().map(|s| match s {
+        MonkeyCStatement::VariableDeclaration { .. } => "Var",
+        MonkeyCStatement::ClassDeclaration { .. } => "Class",
+        MonkeyCStatement::FunctionDeclaration { .. } => "Function",
+        MonkeyCStatement::Return { .. } => "Return",
+        MonkeyCStatement::If { .. } => "If",
+        MonkeyCStatement::Error { .. } => "Error",
+        other => panic!("unexpected statement {:?}", other),
+    }).collect::<Vec<_>>().join(" ")
+}
+
+fn body(function: &MonkeyCStatement) -> &[MonkeyCStatement] {
+    match function {
+        MonkeyCStatement::FunctionDeclaration { body, .. } => match body.as_ref() {
+            MonkeyCStatement::Block { children, .. } => children,
+            other => panic!("expected a block, got {:?}", other),
+        },
+        other => panic!("expected a function, got {:?}", other),
+    }
+}
+
+#[test]
+fn resynchronizes_after_semicolon() {
+    let source = "var a = ;\nvar b = foo(1 2, 3);\nvar c = 3;";
+    let (statements, errors) = parse(source);
+    assert_eq!(kinds(&statements), "Error Error Var");
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    let span = statements[1].span();
+    assert_eq!(&source[span.start..span.end], "var b = foo(1 2, 3);");
+}
+
+#[test]
+fn resynchronizes_at_declaration_keywords() {
+    let (statements, errors) = parse("var a = 1 +\nfunction f() { return 1; }\nclass A { }");
+    assert_eq!(kinds(&statements), "Error Function Class");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].msg, "Expected an expression, found 'function'");
+}
+
+#[test]
+fn errors_stay_inside_of_blocks() {
+    let (statements, errors) = parse("function f() {\n  var d = { :a 1 };\n  if (d) { return 1 }\n  return d;\n}\nvar x = 1;");
+    assert_eq!(kinds(&statements), "Function Var");
+    assert_eq!(kinds(body(&statements[0])), "Error If Return");
+    assert_eq!(errors.iter().map(|e| e.msg.as_str()).collect::<Vec<_>>(),
+               vec!["Expected '=>', found '1'", "Expected ';', found '}'"]);
+}
+
+#[test]
+fn bad_class_members_become_errors() {
+    let (statements, errors) = parse("class A {\n  var x = ;\n  5 + 5;\n  function f() { }\n}");
+    match &statements[..] {
+        [MonkeyCStatement::ClassDeclaration { children, .. }] => assert_eq!(kinds(children), "Error Error Function"),
+        other => panic!("expected a class, got {:?}", other),
+    }
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert_eq!(errors[1].msg, "Expected a class member, found '5'");
+}
+
+#[test]
+fn unclosed_blocks_keep_their_contents() {
+    let (statements, errors) = parse("class A {\n  function f() {\n    return 1;\n");
+    match &statements[..] {
+        [MonkeyCStatement::ClassDeclaration { children, .. }] => {
+            assert_eq!(kinds(children), "Function");
+            assert_eq!(kinds(body(&children[0])), "Return");
+        }
+        other => panic!("expected a class, got {:?}", other),
+    }
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+}
+
+#[test]
+fn stray_tokens_at_the_top_level() {
+    let (statements, errors) = parse("} 5 + 5; ) class A { }");
+    // The first error swallows everything up to the semicolon
+    assert_eq!(kinds(&statements), "Error Error Class");
+    assert_eq!(errors[0].msg, "Expected a declaration, found '}'");
+    assert_eq!(errors[1].msg, "Expected a declaration, found ')'");
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+}
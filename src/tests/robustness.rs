@@ -0,0 +1,85 @@
+use crate::fmt::same_tree;
+use crate::lexer::MonkeyCLexer;
+use crate::lexer::tokens::TokenKind;
+use crate::parser::MonkeyCParser;
+use crate::parser::printer::print;
+use proptest::prelude::*;
+use std::path::PathBuf;
+
+/// Runs both the lexer and the parser on `source` and returns
+/// the number of syntax errors. Any panic fails the test.
+fn lex_and_parse(source: &str) -> usize {
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, _) = lexer.lex();
+    assert_eq!(tokens.last().map(|t| t.kind.clone()), Some(TokenKind::Eof));
+
+    let mut parser = MonkeyCParser::new(tokens, PathBuf::from("<test>"));
+    parser.parse().1.len()
+}
+
+#[test]
+fn truncated_input() {
+    let cases = ["", "var", "var x", "var x =", "var x as", "var x as String =", "var 1", "'", "'\\", "\"", "\"\\",
+                 "\"\\u12", "/*", "/", "0x", "1e", "1.", ":", "?", "var x = 'a", "var x as String = \"Hm\"; var"];
+    for case in cases.iter() {
+        lex_and_parse(case);
+    }
+}
+
+#[test]
+fn deeply_nested_input() {
+    // Deep enough to overflow the stack if every level took a recursive call
+    let depth = 10_000;
+    for (open, close) in [("(", ")"), ("[", "]"), ("{", "}"), ("if (a) {", "}")].iter() {
+        let closed = format!("{}1{}", open.repeat(depth), close.repeat(depth));
+        let unclosed = open.repeat(depth);
+        for nested in [closed, unclosed].iter() {
+            for source in [format!("var x = {};", nested), format!("function f() {{ {} }}", nested)].iter() {
+                assert!(lex_and_parse(source) > 0, "{}", &source[..40]);
+            }
+        }
+    }
+}
+
+#[test]
+fn long_chains_can_be_printed_and_serialized() {
+    let chains = |terms: usize| vec![
+        format!("var x = a{};", "+a".repeat(terms)),
+        format!("var x = a{};", ".b".repeat(terms)),
+        format!("var x = a{};", "[0]".repeat(terms)),
+        format!("var x = f{};", "()".repeat(terms)),
+        format!("var x as Number{};", "?".repeat(terms)),
+    ];
+    for source in chains(90).iter() {
+        let (statements, errors) = crate::parse(source);
+        assert!(errors.is_empty(), "{}: {:?}", &source[..20], errors);
+        assert!(same_tree(&crate::parse(&print(&statements)).0, &statements), "{}", &source[..20]);
+    }
+    // Loops build these without recursion, but everything that walks the tree recurses
+    for source in chains(20_000).iter() {
+        let (statements, errors) = crate::parse(source);
+        assert!(!errors.is_empty(), "{}", &source[..20]);
+        print(&statements);
+        serde_json::to_string(&statements).unwrap();
+    }
+}
+
+#[test]
+fn parser_accepts_tokens_without_eof() {
+    let mut parser = MonkeyCParser::new(Vec::new(), PathBuf::from("<test>"));
+    let (statements, errors) = parser.parse();
+    assert!(statements.is_empty());
+    assert!(errors.is_empty());
+}
+
+proptest! {
+    #[test]
+    fn never_panics_on_arbitrary_strings(source in any::<String>()) {
+        lex_and_parse(&source);
+    }
+
+    #[test]
+    fn never_panics_on_code_like_strings(source in "(var|as|class|function|[a-zA-Z0-9_]|[ \\n\\t]|[\"'\\\\/*:;,.?{}()\\[\\]=<>!&|+-]){0,40}") {
+        lex_and_parse(&source);
+    }
+}
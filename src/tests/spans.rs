@@ -0,0 +1,63 @@
+use crate::lexer::MonkeyCLexer;
+use crate::lexer::tokens::TokenKind;
+use crate::span::{LineIndex, Span};
+
+#[test]
+fn tokens_have_byte_spans() {
+    let data = "var é = \"日本\"; // ok\n:sym";
+    let mut lexer = MonkeyCLexer::with_file_id(data.chars().collect(), 3);
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let spans: Vec<(&str, Span)> = tokens.iter().map(|t| (&data[t.span.start..t.span.end], t.span)).collect();
+    assert_eq!(spans, vec![
+        ("var", Span::new(3, 0, 3)),
+        ("é", Span::new(3, 4, 6)),
+        ("=", Span::new(3, 7, 8)),
+        ("\"日本\"", Span::new(3, 9, 17)),
+        (";", Span::new(3, 17, 18)),
+        (":sym", Span::new(3, 25, 29)),
+        ("", Span::new(3, 29, 29)),
+    ]);
+    // Trivia on the same line as a token trails it
+    let comment = &tokens[4].trailing_trivia[1];
+    assert_eq!(&data[comment.span.start..comment.span.end], "// ok");
+}
+
+#[test]
+fn line_index_maps_offsets_back() {
+    let data = "a\r\nb😀c\n\nd";
+    let index = LineIndex::new(data);
+
+    assert_eq!(index.line_col(0), (1, 1));
+    assert_eq!(index.line_col(3), (2, 1));
+    // After the emoji, which is one character, two UTF-16 code units and four bytes
+    assert_eq!(index.line_col(8), (2, 3));
+    assert_eq!(index.line_col_utf16(8), (2, 4));
+    assert_eq!(index.line_col(10), (3, 1));
+    assert_eq!(index.line_col(11), (4, 1));
+    assert_eq!(index.line_col(100), (4, 2));
+
+    assert_eq!(index.line_text(1), "a");
+    assert_eq!(index.line_text(2), "b😀c");
+    assert_eq!(index.line_text(3), "");
+}
+
+#[test]
+fn line_index_agrees_with_the_lexer_on_carriage_returns() {
+    let data = "var a;\rvar b;\r\n\rvar c; /* a\rb\r\nc */ var d;";
+    let index = LineIndex::new(data);
+    assert_eq!(index.line_text(1), "var a;");
+    assert_eq!(index.line_text(2), "var b;");
+    assert_eq!(index.line_text(3), "");
+    assert_eq!(index.line_text(4), "var c; /* a");
+    assert_eq!(index.line_text(6), "c */ var d;");
+
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, _) = lexer.lex();
+    let vars: Vec<(u64, u64)> = tokens.iter().filter(|t| t.kind == TokenKind::Var).map(|t| (t.row, t.column)).collect();
+    assert_eq!(vars, vec![(1, 1), (2, 1), (4, 1), (6, 6)]);
+    for token in tokens.iter().filter(|t| t.kind == TokenKind::Var) {
+        assert_eq!(index.line_col(token.span.start), (token.row, token.column));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use crate::parser::ast::{MonkeyCCatch, MonkeyCExpression, MonkeyCStatement};
+use crate::parser::err::MCParseError;
+use crate::tests::expressions::sexpr;
+use std::path::PathBuf;
+
+/// Parses `source` as the body of a function
+/// and returns the statements in it
+fn parse_body_with_errors(source: &str) -> (Vec<MonkeyCStatement>, Vec<MCParseError>) {
+    let data = format!("function f() {{\n{}\n}}", source);
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let (mut statements, errors) = MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse();
+    match statements.pop() {
+        Some(MonkeyCStatement::FunctionDeclaration { body, .. }) => match *body {
+            MonkeyCStatement::Block { children, .. } => (children, errors),
+            other => panic!("expected a block, got {:?}", other),
+        },
+        other => panic!("expected a function, got {:?}", other),
+    }
+}
+
+fn parse_body(source: &str) -> Vec<MonkeyCStatement> {
+    let (statements, errors) = parse_body_with_errors(source);
+    assert!(errors.is_empty(), "{:?}", errors);
+    statements
+}
+
+fn is_reference(expression: &MonkeyCExpression, expected: &str) -> bool {
+    matches!(expression, MonkeyCExpression::Reference { name, .. } if name == expected)
+}
+
+#[test]
+fn if_else_chains() {
+    let statements = parse_body("if (a) { return; } else if (b) return 1; else { throw e; }");
+    match &statements[..] {
+        [MonkeyCStatement::If { condition, then_branch, else_branch: Some(else_branch), .. }] => {
+            assert!(is_reference(condition, "a"));
+            assert!(matches!(then_branch.as_ref(), MonkeyCStatement::Block { children, .. }
+                if matches!(&children[..], [MonkeyCStatement::Return { value: None, .. }])));
+            match else_branch.as_ref() {
+                MonkeyCStatement::If { condition, then_branch, else_branch: Some(last), .. } => {
+                    assert!(is_reference(condition, "b"));
+                    assert!(matches!(then_branch.as_ref(), MonkeyCStatement::Return { value: Some(_), .. }));
+                    assert!(matches!(last.as_ref(), MonkeyCStatement::Block { children, .. }
+                        if matches!(&children[..], [MonkeyCStatement::Throw { .. }])));
+                }
+                other => panic!("expected an if, got {:?}", other),
+            }
+        }
+        other => panic!("expected a single if, got {:?}", other),
+    }
+}
+
+#[test]
+fn symbols_after_nullable_casts() {
+    // The `?` made the lexer take `:sym` for the end of a ternary
+    let statements = parse_body("if (x as Foo?) :sym.toString();");
+    match &statements[..] {
+        [MonkeyCStatement::If { then_branch, .. }] => match then_branch.as_ref() {
+            MonkeyCStatement::ExpressionStatement { expression, .. } => assert_eq!(sexpr(expression), "(call (. sym toString) [])"),
+            other => panic!("expected an expression statement, got {:?}", other),
+        },
+        other => panic!("expected a single if, got {:?}", other),
+    }
+}
+
+#[test]
+fn loops() {
+    let statements = parse_body("while (i < 10) { i++; continue; }\ndo { break; } while (false);\n\
+                                 for (var i = 0; i < n; i++) {}\nfor (;;) {}");
+    assert_eq!(statements.len(), 4);
+    assert!(matches!(&statements[0], MonkeyCStatement::While { body, .. }
+        if matches!(body.as_ref(), MonkeyCStatement::Block { children, .. } if matches!(&children[..], [MonkeyCStatement::ExpressionStatement { .. }, MonkeyCStatement::Continue { .. }]))));
+    assert!(matches!(&statements[1], MonkeyCStatement::DoWhile { body, condition: MonkeyCExpression::Simple { .. }, .. }
+        if matches!(body.as_ref(), MonkeyCStatement::Block { children, .. } if matches!(&children[..], [MonkeyCStatement::Break { .. }]))));
+    match &statements[2] {
+        MonkeyCStatement::For { init: Some(init), condition: Some(_), update: Some(MonkeyCExpression::Update { .. }), .. } =>
+            assert!(matches!(init.as_ref(), MonkeyCStatement::VariableDeclaration { declarators, .. } if declarators[0].name == "i")),
+        other => panic!("expected a for loop, got {:?}", other),
+    }
+    assert!(matches!(&statements[3], MonkeyCStatement::For { init: None, condition: None, update: None, .. }));
+}
+
+#[test]
+fn switch_cases() {
+    let statements = parse_body("switch (key) {\ncase :up:\ncase :down:\n  move();\n  break;\ndefault:\n  return false;\n}");
+    match &statements[..] {
+        [MonkeyCStatement::Switch { value, cases, .. }] => {
+            assert!(is_reference(value, "key"));
+            assert_eq!(cases.len(), 3);
+            assert!(matches!(&cases[0].value, Some(MonkeyCExpression::Simple { value, .. }) if value == "up"));
+            assert!(cases[0].children.is_empty());
+            assert!(matches!(&cases[1].children[..], [MonkeyCStatement::ExpressionStatement { .. }, MonkeyCStatement::Break { .. }]));
+            assert_eq!(cases[2].value, None);
+            assert!(matches!(&cases[2].children[..], [MonkeyCStatement::Return { .. }]));
+        }
+        other => panic!("expected a single switch, got {:?}", other),
+    }
+}
+
+#[test]
+fn try_catch_finally() {
+    let statements = parse_body("try { connect(); }\ncatch (e instanceof Lang.Exception) { }\ncatch (e) { }\nfinally { }");
+    match &statements[..] {
+        [MonkeyCStatement::Try { catches, finally: Some(_), .. }] => {
+            assert_eq!(catches.len(), 2);
+            let MonkeyCCatch { binding, class, .. } = &catches[0];
+            assert_eq!((binding.as_str(), class.as_deref()), ("e", Some("Lang.Exception")));
+            assert_eq!(catches[1].class, None);
+        }
+        other => panic!("expected a single try, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_statements_are_reported() {
+    let cases = [("if a { }", "Expected '(', found 'a'"),
+                 ("while (a { }", "Expected ')', found '{'"),
+                 ("do { } (a);", "Expected 'while', found '('"),
+                 ("for (var i = 0; i < n; i++ { }", "Expected ')', found '{'"),
+                 ("switch (a) { foo(); }", "Expected 'case' or 'default', found 'foo'"),
+                 ("try { }", "Expected 'catch' or 'finally', found '}'"),
+                 ("return 1", "Expected ';', found '}'"),
+                 ("if (a)", "Expected a statement, found '}'")];
+    for (source, message) in cases.iter() {
+        let (_, errors) = parse_body_with_errors(source);
+        assert_eq!(errors[0].msg, *message, "{}", source);
+    }
+}
+
+#[test]
+fn expression_statements_and_assignments() {
+    let statements = parse_body("foo = bar + 1;\ncount += 1;\ni++;\nview.requestUpdate();\nself.items[i] <<= 2;\n\
+                                 for (i = 0; i < n; i += 2) { }");
+    let expressions: Vec<String> = statements.iter().map(|s| match s {
+        MonkeyCStatement::ExpressionStatement { expression, .. } => sexpr(expression),
+        MonkeyCStatement::For { init: Some(init), update: Some(update), .. } => match init.as_ref() {
+            MonkeyCStatement::ExpressionStatement { expression, .. } => format!("for {} {}", sexpr(expression), sexpr(update)),
+            other => panic!("expected an expression statement, got {:?}", other),
+        },
+        other => panic!("expected an expression statement, got {:?}", other),
+    }).collect();
+    assert_eq!(expressions, vec![
+        "(=Assign foo (Add bar 1))",
+        "(=Add count 1)",
+        "(Increment i)",
+        "(call (. view requestUpdate) [])",
+        "(=LeftShift ([] (. self items) i) 2)",
+        "for (=Assign i 0) (=Add i 2)",
+    ]);
+}
+
+#[test]
+fn assignment_targets_are_validated() {
+    let cases = ["1 = 2;", "foo() = 1;", "a + b += 1;", "-a = 1;"];
+    for source in cases.iter() {
+        let (_, errors) = parse_body_with_errors(source);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].msg, "Invalid assignment target");
+        assert_eq!(errors[0].full_msg, "Syntax error at <test>:2:1: Invalid assignment target");
+    }
+    // The whole target is underlined
+    let (_, errors) = parse_body_with_errors("foo() = 1;");
+    assert_eq!((errors[0].span.start, errors[0].span.end), (15, 20));
+    // Brackets don't change what's assigned to
+    parse_body("(a) = 1;");
+}
+
+#[test]
+fn deeply_nested_statements_are_reported() {
+    let nested = |open: &str, close: &str, depth: usize| format!("{}x();{}", open.repeat(depth), close.repeat(depth));
+    assert_eq!(parse_body(&nested("if (a) {", "}", 30)).len(), 1);
+
+    for source in [nested("{", "}", 10_000), nested("if (a) {", "}", 10_000), nested("while (a) ", "", 10_000)].iter() {
+        let (_, errors) = parse_body_with_errors(source);
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|e| e.msg.ends_with("is nested too deeply")), "{:?}", errors);
+    }
+}
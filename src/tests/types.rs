@@ -0,0 +1,97 @@
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use crate::parser::ast::{MonkeyCExpression, MonkeyCStatement, MonkeyCTypeExpression};
+use std::path::PathBuf;
+
+/// Writes the type back in Monkey C syntax, with
+/// parentheses around nested unions
+pub(super) fn show_type(type_expression: &MonkeyCTypeExpression) -> String {
+    let list = |types: &[MonkeyCTypeExpression]| types.iter().map(show_type).collect::<Vec<_>>().join(", ");
+    match type_expression {
+        MonkeyCTypeExpression::Named { name, generics, .. } if generics.is_empty() => name.clone(),
+        MonkeyCTypeExpression::Named { name, generics, .. } => format!("{}<{}>", name, list(generics)),
+        MonkeyCTypeExpression::Union { types, .. } =>
+            format!("({})", types.iter().map(show_type).collect::<Vec<_>>().join(" or ")),
+        MonkeyCTypeExpression::Nullable { inner, .. } => format!("{}?", show_type(inner)),
+        MonkeyCTypeExpression::Callback { params, return_type, .. } => {
+            let params: Vec<String> = params.iter().map(|p| match &p.param_type {
+                Some(t) => format!("{} as {}", p.name, show_type(t)),
+                None => p.name.clone(),
+            }).collect();
+            match return_type {
+                Some(t) => format!("Method({}) as {}", params.join(", "), show_type(t)),
+                None => format!("Method({})", params.join(", ")),
+            }
+        }
+        MonkeyCTypeExpression::DictionaryShape { entries, .. } => format!("{{{}}}", entries.iter().map(|(key, t)| match key {
+            MonkeyCExpression::Simple { value, .. } => format!("{} as {}", value, show_type(t)),
+            other => panic!("unexpected key {:?}", other),
+        }).collect::<Vec<_>>().join(", ")),
+        MonkeyCTypeExpression::Tuple { types, .. } => format!("[{}]", list(types)),
+    }
+}
+
+fn parse_var_type(source: &str) -> Result<String, String> {
+    let data = format!("var x as {} = null;", source);
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, errors) = lexer.lex();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let (statements, errors) = MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse();
+    if let Some(error) = errors.first() {
+        return Err(error.msg.clone());
+    }
+    match &statements[..] {
+        [MonkeyCStatement::VariableDeclaration { declarators, .. }] => Ok(show_type(declarators[0].var_type.as_ref().unwrap())),
+        other => panic!("expected a typed variable, got {:?}", other),
+    }
+}
+
+#[test]
+fn type_annotations() {
+    let cases = [
+        ("Lang.Number", "Lang.Number"),
+        ("Number or Null", "(Number or Null)"),
+        ("Array<Number>", "Array<Number>"),
+        ("Dictionary<String, Array<Number or String>>", "Dictionary<String, Array<(Number or String)>>"),
+        ("Array<Array<Number>>", "Array<Array<Number>>"),
+        ("Lang.Object?", "Lang.Object?"),
+        ("Method(x as Number, y) as Void", "Method(x as Number, y) as Void"),
+        ("Method() or Null", "(Method() or Null)"),
+        ("{ :a as Number, \"b\" as String? }", "{a as Number, b as String?}"),
+        ("[Number, String]", "[Number, String]"),
+    ];
+    for (source, expected) in cases.iter() {
+        assert_eq!(parse_var_type(source).as_deref(), Ok(*expected), "{}", source);
+    }
+}
+
+#[test]
+fn generics_split_shift_tokens() {
+    // `>>=` has to become `>`, `>` and `=`
+    assert_eq!(parse_var_type("Array<Array<Number>>").as_deref(), Ok("Array<Array<Number>>"));
+    let data = "var x as Array<Array<Number>>= null;";
+    let mut lexer = MonkeyCLexer::new(data.chars().collect());
+    let (tokens, _) = lexer.lex();
+    let (statements, errors) = MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse();
+    assert!(errors.is_empty(), "{:?}", errors);
+    match &statements[..] {
+        [MonkeyCStatement::VariableDeclaration { declarators, .. }] => {
+            let t = declarators[0].var_type.as_ref().unwrap();
+            assert_eq!(show_type(t), "Array<Array<Number>>");
+            assert_eq!((t.span().start, t.span().end), (9, 29));
+        }
+        other => panic!("expected a typed variable, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_types_are_reported() {
+    let cases = [("5", "Expected a type, found '5'"),
+                 ("Array<Number", "Expected '>', found '='"),
+                 ("{ a as Number }", "Expected a symbol or a string, found 'a'"),
+                 ("{ :a Number }", "Expected 'as', found 'Number'"),
+                 ("[Number String]", "Expected ',' or ']', found 'String'")];
+    for (source, message) in cases.iter() {
+        assert_eq!(parse_var_type(source), Err(message.to_string()), "{}", source);
+    }
+}
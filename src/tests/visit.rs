@@ -0,0 +1,112 @@
+use crate::lexer::MonkeyCLexer;
+use crate::parser::MonkeyCParser;
+use crate::parser::ast::{MonkeyCAnnotation, MonkeyCExpression, MonkeyCStatement, MonkeyCTypeExpression};
+use crate::parser::visit::{walk_expression, walk_expression_mut, walk_statement, walk_type, Visitor, VisitorMut};
+use std::path::PathBuf;
+
+fn parse(source: &str) -> Vec<MonkeyCStatement> {
+    let mut lexer = MonkeyCLexer::new(source.chars().collect());
+    let (tokens, _) = lexer.lex();
+    let (statements, errors) = MonkeyCParser::new(tokens, PathBuf::from("<test>")).parse();
+    assert!(errors.is_empty(), "{:?}", errors);
+    statements
+}
+
+/// Writes down everything it passes, in order
+#[derive(Default)]
+struct Recorder {
+    seen: Vec<String>,
+}
+
+impl Visitor for Recorder {
+    fn visit_statement(&mut self, statement: &MonkeyCStatement) {
+        match statement {
+            MonkeyCStatement::FunctionDeclaration { name, .. } => self.seen.push(format!("function {}", name)),
+            MonkeyCStatement::Return { .. } => self.seen.push("return".to_string()),
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &MonkeyCExpression) {
+        if let MonkeyCExpression::Reference { name, .. } = expression {
+            self.seen.push(name.clone());
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_type(&mut self, type_expression: &MonkeyCTypeExpression) {
+        if let MonkeyCTypeExpression::Named { name, .. } = type_expression {
+            self.seen.push(format!("type {}", name));
+        }
+        walk_type(self, type_expression);
+    }
+
+    fn visit_annotation(&mut self, annotation: &MonkeyCAnnotation) {
+        self.seen.push(format!("annotation {}", annotation.name));
+    }
+}
+
+#[test]
+fn visitor_reaches_every_kind_of_node() {
+    let source = "(:background) module M {\n\
+                  enum { A = a }\n\
+                  class C {\n\
+                  var x as Array<Number> = [b, { c => d }];\n\
+                  (:test) function f(p as Method(q as Q) as R) as Number {\n\
+                  if (e) { g(h as Lang.String); } else { i = j ? k : l; }\n\
+                  for (var n = o; p < q; r++) { while (s) { do { t; } while (u); } }\n\
+                  switch (v) { case w: x; default: return y; }\n\
+                  try { throw z; } catch (ex) { new Foo(aa)[bb]; } finally { cc.dd; }\n\
+                  return ee has :ff;\n\
+                  }\n}\n}";
+    let mut recorder = Recorder::default();
+    for statement in &parse(source) {
+        recorder.visit_statement(statement);
+    }
+    let expected = "annotation background a type Array type Number b c d \
+                    function f annotation test type Q type R type Number \
+                    e g h type Lang.String i j k l o p q r s t u v w x return y \
+                    z Foo aa bb cc return ee";
+    assert_eq!(recorder.seen.join(" "), expected);
+}
+
+#[test]
+fn overridden_nodes_are_not_walked_automatically() {
+    struct Counter(usize);
+    impl Visitor for Counter {
+        fn visit_expression(&mut self, _: &MonkeyCExpression) {
+            self.0 += 1;
+        }
+    }
+    let mut counter = Counter(0);
+    for statement in &parse("function f() { a(b, c); d + e; }") {
+        counter.visit_statement(statement);
+    }
+    // Only the roots of both expressions
+    assert_eq!(counter.0, 2);
+}
+
+#[test]
+fn mutable_visitor_rewrites_the_tree() {
+    struct Renamer;
+    impl VisitorMut for Renamer {
+        fn visit_expression_mut(&mut self, expression: &mut MonkeyCExpression) {
+            if let MonkeyCExpression::Reference { name, .. } = expression {
+                if name == "old" {
+                    *name = "new".to_string();
+                }
+            }
+            walk_expression_mut(self, expression);
+        }
+    }
+    let mut statements = parse("var a = old + 1;\nfunction f() { return old(other); }");
+    for statement in &mut statements {
+        Renamer.visit_statement_mut(statement);
+    }
+    let mut recorder = Recorder::default();
+    for statement in &statements {
+        recorder.visit_statement(statement);
+    }
+    assert_eq!(recorder.seen.join(" "), "new function f return new other");
+}